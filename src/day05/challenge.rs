@@ -5,6 +5,27 @@ use cpu::{
 };
 use std::collections::HashMap;
 
+use crate::intcode::conformance::{
+    Report,
+    Run,
+    check_with
+};
+use crate::intcode::coverage::run_coverage;
+use crate::intcode::cpu::CPU as SharedCPU;
+use crate::intcode::cpu::Computer as SharedComputer;
+use crate::intcode::devices::{
+    IteratorConsumer,
+    VecProducer
+};
+use crate::intcode::fuzz::{
+    FuzzConfig,
+    Fuzzer
+};
+use crate::intcode::optimise::{
+    OptimiseOptions,
+    optimise
+};
+
 fn process_input( input: &str ) -> HashMap<usize, i64> {
     return input.split( "," )
         .enumerate()
@@ -12,12 +33,124 @@ fn process_input( input: &str ) -> HashMap<usize, i64> {
         .collect();
 }
 
+const FUZZ_USAGE: &str = "usage: day_05 fuzz [seed] [iterations] [corpus directory]";
+
+fn fuzz( opcodes: HashMap<usize, i64>, args: &[String] ) {
+    let seed: u64 = match args.get( 2 ).map( |v| v.parse() ) {
+        None => 1,
+        Some( Ok( seed ) ) => seed,
+        Some( Err( _ ) ) => {
+            println!( "{}", FUZZ_USAGE );
+            return;
+        }
+    };
+
+    let iterations: usize = match args.get( 3 ).map( |v| v.parse() ) {
+        None => 10_000,
+        Some( Ok( iterations ) ) => iterations,
+        Some( Err( _ ) ) => {
+            println!( "{}", FUZZ_USAGE );
+            return;
+        }
+    };
+
+    let config = FuzzConfig { seed: seed, max_inputs: 4, instruction_limit: 100_000 };
+
+    let mut fuzzer = Fuzzer::initialise( opcodes, config );
+    let stats = fuzzer.run( iterations );
+
+    println!( "{} executions, {} addresses covered, {} distinct outputs",
+        stats.executions, stats.addresses_covered, stats.distinct_outputs );
+    println!( "{} cases in the corpus, {} crashes", stats.corpus_size, stats.crashes );
+
+    if let Some( directory ) = args.get( 4 ) {
+        fuzzer.save_corpus( directory ).expect( "failed to save corpus" );
+    }
+}
+
+// outputs and instructions executed on the shared cpu
+fn run_counting( opcodes: &HashMap<usize, i64>, input: i64 ) -> ( Vec<i64>, u64 ) {
+    let mut cpu = SharedCPU::initialise( opcodes.clone(), IteratorConsumer::initialise( vec![ input ] ), VecProducer::initialise() );
+    cpu.display_std_out( false );
+    cpu.execute_instructions();
+
+    return ( cpu.get_outputs_producer().take(), cpu.get_step_count() );
+}
+
+fn optimise_test_program( opcodes: &HashMap<usize, i64> ) {
+    let ( optimised, report ) = optimise( opcodes, &OptimiseOptions::default() );
+
+    println!( "{} constants folded, {} no-ops removed, {} jumps threaded, {} cells shorter",
+        report.folded, report.noops_removed, report.jumps_threaded, report.cells_removed );
+
+    for input in [ 1, 5 ].iter() {
+        let ( outputs, steps ) = run_counting( opcodes, *input );
+        let ( optimised_outputs, optimised_steps ) = run_counting( &optimised, *input );
+
+        println!( "system {}: {:?} in {} steps, optimised {:?} in {} steps", input, outputs, steps, optimised_outputs, optimised_steps );
+    }
+}
+
+// this copy runs to the end in one go, so outputs are collected rather than paused on
+fn conformance() -> Report {
+    return check_with( 5, |case| {
+        let mut cpu: CPU = Computer::initialise( process_input( case.program ) );
+
+        // set_inputs hands them out from the back
+        cpu.set_inputs( case.inputs.iter().rev().cloned().collect() );
+        cpu.display_std_out( false );
+        cpu.execute_instructions();
+
+        return Run { outputs: cpu.get_outputs().clone(), memory: Some( cpu.get_memory().clone() ) };
+    } );
+}
+
 #[allow(dead_code)]
-pub fn day_05( _args: Vec<String>) {
+pub fn day_05( args: Vec<String>) {
+    // day_05 conformance: run the published example programs through this day's cpu
+    if args.get( 1 ).map( |a| a == "conformance" ).unwrap_or( false ) {
+        println!( "day 05 cpu\n{}", conformance() );
+        return;
+    }
+
     let input = "3,225,1,225,6,6,1100,1,238,225,104,0,1102,83,20,225,1102,55,83,224,1001,224,-4565,224,4,224,102,8,223,223,101,5,224,224,1,223,224,223,1101,52,15,225,1102,42,92,225,1101,24,65,225,101,33,44,224,101,-125,224,224,4,224,102,8,223,223,1001,224,7,224,1,223,224,223,1001,39,75,224,101,-127,224,224,4,224,1002,223,8,223,1001,224,3,224,1,223,224,223,2,14,48,224,101,-1300,224,224,4,224,1002,223,8,223,1001,224,2,224,1,223,224,223,1002,139,79,224,101,-1896,224,224,4,224,102,8,223,223,1001,224,2,224,1,223,224,223,1102,24,92,225,1101,20,53,224,101,-73,224,224,4,224,102,8,223,223,101,5,224,224,1,223,224,223,1101,70,33,225,1101,56,33,225,1,196,170,224,1001,224,-38,224,4,224,102,8,223,223,101,4,224,224,1,224,223,223,1101,50,5,225,102,91,166,224,1001,224,-3003,224,4,224,102,8,223,223,101,2,224,224,1,224,223,223,4,223,99,0,0,0,677,0,0,0,0,0,0,0,0,0,0,0,1105,0,99999,1105,227,247,1105,1,99999,1005,227,99999,1005,0,256,1105,1,99999,1106,227,99999,1106,0,265,1105,1,99999,1006,0,99999,1006,227,274,1105,1,99999,1105,1,280,1105,1,99999,1,225,225,225,1101,294,0,0,105,1,0,1105,1,99999,1106,0,300,1105,1,99999,1,225,225,225,1101,314,0,0,106,0,0,1105,1,99999,1107,677,677,224,1002,223,2,223,1006,224,329,1001,223,1,223,1107,226,677,224,102,2,223,223,1005,224,344,101,1,223,223,108,677,677,224,1002,223,2,223,1006,224,359,101,1,223,223,107,677,677,224,1002,223,2,223,1006,224,374,1001,223,1,223,1007,677,677,224,102,2,223,223,1006,224,389,101,1,223,223,108,677,226,224,102,2,223,223,1006,224,404,101,1,223,223,1108,226,677,224,102,2,223,223,1005,224,419,1001,223,1,223,7,677,226,224,102,2,223,223,1005,224,434,101,1,223,223,1008,677,677,224,102,2,223,223,1006,224,449,1001,223,1,223,1007,677,226,224,1002,223,2,223,1006,224,464,101,1,223,223,1108,677,677,224,1002,223,2,223,1005,224,479,1001,223,1,223,107,226,226,224,1002,223,2,223,1005,224,494,101,1,223,223,8,226,677,224,102,2,223,223,1006,224,509,101,1,223,223,8,677,677,224,102,2,223,223,1006,224,524,101,1,223,223,1007,226,226,224,1002,223,2,223,1006,224,539,1001,223,1,223,107,677,226,224,102,2,223,223,1006,224,554,101,1,223,223,1107,677,226,224,1002,223,2,223,1006,224,569,1001,223,1,223,1008,226,677,224,102,2,223,223,1006,224,584,1001,223,1,223,1008,226,226,224,1002,223,2,223,1005,224,599,1001,223,1,223,7,677,677,224,1002,223,2,223,1005,224,614,1001,223,1,223,1108,677,226,224,1002,223,2,223,1005,224,629,101,1,223,223,7,226,677,224,1002,223,2,223,1005,224,644,1001,223,1,223,8,677,226,224,102,2,223,223,1005,224,659,101,1,223,223,108,226,226,224,102,2,223,223,1005,224,674,101,1,223,223,4,223,99,226";
     let opcodes = process_input( input );
+
+    // day_05 fuzz [seed] [iterations] [corpus directory]
+    if args.get( 1 ).map( |a| a == "fuzz" ).unwrap_or( false ) {
+        fuzz( opcodes, &args );
+        return;
+    }
+
+    // day_05 coverage [report file]: which parts of TEST ran for system ids 1 and 5
+    if args.get( 1 ).map( |a| a == "coverage" ).unwrap_or( false ) {
+        let coverage = run_coverage( &opcodes, &[ vec![ 1 ], vec![ 5 ] ] );
+
+        match args.get( 2 ) {
+            Some( path ) => coverage.write_report( &opcodes, path ).expect( "failed to write the report" ),
+            None => print!( "{}", coverage.report( &opcodes ) )
+        }
+
+        return;
+    }
+
+    // day_05 optimise: optimise the diagnostic program and compare it with the original
+    if args.get( 1 ).map( |a| a == "optimise" ).unwrap_or( false ) {
+        optimise_test_program( &opcodes );
+        return;
+    }
+
     let mut cpu: CPU = Computer::initialise( opcodes );
 
     cpu.execute_instructions();
     // cpu.print_initial_value();
 }
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn cpu_passes_conformance() {
+        let report = super::conformance();
+        assert!( report.is_success(), "{}", report );
+    }
+}
@@ -18,12 +18,19 @@ pub trait Computer {
     fn execute_instruction( &mut self );
     fn execute_instructions( &mut self );
     fn print_initial_value( &mut self );
+    fn set_inputs( &mut self, inputs: Vec<i64> );
+    fn display_std_out( &mut self, disp_std_out: bool );
+    fn get_outputs( &self ) -> &Vec<i64>;
+    fn get_memory( &self ) -> &HashMap< usize, i64 >;
 }
 
 pub struct CPU {
     dispatcher: HashMap<usize, Instruction>,
     memory: HashMap<usize, i64>,
-    idx: usize
+    idx: usize,
+    inputs: Vec<i64>,
+    outputs: Vec<i64>,
+    disp_std_out: bool
 }
 
 impl Computer for CPU {
@@ -39,7 +46,7 @@ impl Computer for CPU {
         dispatcher.insert( 8, Self::equals as Instruction );
         dispatcher.insert( 99, Self::exit as Instruction );
 
-        CPU { dispatcher: dispatcher, memory: memory, idx: 0 }
+        CPU { dispatcher: dispatcher, memory: memory, idx: 0, inputs: Vec::new(), outputs: Vec::new(), disp_std_out: true }
     }
 
     fn execute_instructions( &mut self ) {
@@ -108,7 +115,7 @@ impl Computer for CPU {
         }
 
 
-        let output = first_val + second_val;
+        let output = first_val.checked_add( second_val ).unwrap_or_else( || panic!( "arithmetic overflow in instruction at idx {}", self.idx ) );
 
         // write into memory
         let output_save = self.memory[&(self.idx + 3)] as usize;
@@ -141,7 +148,7 @@ impl Computer for CPU {
             }
         }
 
-        let output = first_val * second_val;
+        let output = first_val.checked_mul( second_val ).unwrap_or_else( || panic!( "arithmetic overflow in instruction at idx {}", self.idx ) );
 
         // write into memory
         let output_save = self.memory[&(self.idx + 3)] as usize;
@@ -153,12 +160,18 @@ impl Computer for CPU {
     fn read( &mut self, _: Option<i64>, _: Option<i64> ) {
         let save_location = self.memory[&(self.idx + 1)] as usize;
         let mut buff = String::new();
+        let input: i64;
 
-        print!( "Enter a value: " );
-        io::stdout().flush().ok().expect( "could not flush" );
-        io::stdin().read_line( &mut buff ).expect( "failed to read line" );
+        if self.inputs.len() > 0 {
+            input = self.inputs.pop().unwrap();
+        } else {
+            print!( "Enter a value: " );
+            io::stdout().flush().ok().expect( "could not flush" );
+            io::stdin().read_line( &mut buff ).expect( "failed to read line" );
+            input = buff.trim().parse().unwrap();
+        }
 
-        *self.memory.get_mut( &save_location ).unwrap() = buff.trim().parse().unwrap();
+        *self.memory.get_mut( &save_location ).unwrap() = input;
         self.idx += 2;
     }
 
@@ -175,8 +188,11 @@ impl Computer for CPU {
             }
         }
 
-        println!( "{}", value );
+        if self.disp_std_out {
+            println!( "{}", value );
+        }
 
+        self.outputs.push( value );
         self.idx += 2;
     }
 
@@ -317,4 +333,20 @@ impl Computer for CPU {
     fn print_initial_value( &mut self ) {
         println!( "{}", self.memory[&0] );
     }
+
+    fn set_inputs( &mut self, inputs: Vec<i64> ) {
+        self.inputs = inputs;
+    }
+
+    fn display_std_out( &mut self, disp_std_out: bool ) {
+        self.disp_std_out = disp_std_out;
+    }
+
+    fn get_outputs( &self ) -> &Vec<i64> {
+        return &self.outputs;
+    }
+
+    fn get_memory( &self ) -> &HashMap< usize, i64 > {
+        return &self.memory;
+    }
 }
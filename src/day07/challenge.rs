@@ -4,6 +4,12 @@ use self::cpu::{
     CPU,
     Computer
 };
+use crate::intcode::conformance::Report;
+use crate::intcode::batch::{
+    BatchJob,
+    run_batch,
+    default_workers
+};
 use std::collections::HashMap;
 use itertools::Itertools;
 
@@ -15,30 +21,37 @@ fn process_input( input: &str ) -> HashMap<usize, i64> {
 }
 
 fn part_one( opcodes: HashMap<usize, i64> ) {
-    let mut cpu: CPU;
-    let mut next_input;
-    let mut output = 0;
     let num_amplifiers = 5;
-
-    for phase_setting in (0..=4).permutations(5) {
-        let mut phase = phase_setting.clone();
-        next_input = 0;
-
-        for _ in 0..num_amplifiers {
-            cpu = Computer::initialise( opcodes.clone() );
-            cpu.display_std_out( false );
-            cpu.set_inputs( vec![next_input, phase.pop().unwrap()] );
-            cpu.execute_instructions();
-
-            next_input = cpu.get_output_value();
+    let phase_settings: Vec<Vec<i64>> = (0..=4).permutations(5).collect();
+    let mut signals: Vec<i64> = vec![0; phase_settings.len()];
+
+    // every permutation moves through the amplifiers together, one batch per amplifier
+    for amplifier in 0..num_amplifiers {
+        let jobs: Vec<BatchJob> = phase_settings.iter()
+            .zip( signals.iter() )
+            .map( |(phase, signal)| BatchJob::with_inputs( vec![phase[amplifier], *signal] ) )
+            .collect();
+
+        let mut next_signals = Vec::new();
+
+        for result in run_batch( &opcodes, jobs, default_workers(), None ) {
+            match ( result.error, result.outputs.first() ) {
+                ( None, Some( signal ) ) => next_signals.push( *signal ),
+                ( Some( error ), _ ) => {
+                    println!( "Part One: amplifier {} stopped: {}", amplifier, error );
+                    return;
+                },
+                ( None, None ) => {
+                    println!( "Part One: amplifier {} halted without a signal", amplifier );
+                    return;
+                }
+            }
         }
 
-        if next_input > output {
-            output = next_input;
-        }
+        signals = next_signals;
     }
 
-    println!( "Part One: {}", output );
+    println!( "Part One: {}", signals.iter().max().unwrap() );
 }
 
 fn part_two( opcodes: HashMap<usize, i64> ) {
@@ -95,8 +108,18 @@ fn part_two( opcodes: HashMap<usize, i64> ) {
     println!( "Part Two: {}", output );
 }
 
+fn conformance() -> Report {
+    return crate::check_day_cpu!( CPU, 5 );
+}
+
 #[allow(dead_code)]
-pub fn day_07( _args: Vec<String> ) {
+pub fn day_07( args: Vec<String> ) {
+    // day_07 conformance: run the published example programs through this day's cpu
+    if args.get( 1 ).map( |a| a == "conformance" ).unwrap_or( false ) {
+        println!( "day 07 cpu\n{}", conformance() );
+        return;
+    }
+
     let input = "3,8,1001,8,10,8,105,1,0,0,21,30,55,76,97,114,195,276,357,438,99999,3,9,102,3,9,9,4,9,99,3,9,1002,9,3,9,1001,9,5,9,1002,9,2,9,1001,9,2,9,102,2,9,9,4,9,99,3,9,1002,9,5,9,1001,9,2,9,102,5,9,9,1001,9,4,9,4,9,99,3,9,1001,9,4,9,102,5,9,9,101,4,9,9,1002,9,4,9,4,9,99,3,9,101,2,9,9,102,4,9,9,1001,9,5,9,4,9,99,3,9,1002,9,2,9,4,9,3,9,102,2,9,9,4,9,3,9,102,2,9,9,4,9,3,9,1001,9,1,9,4,9,3,9,102,2,9,9,4,9,3,9,1002,9,2,9,4,9,3,9,102,2,9,9,4,9,3,9,102,2,9,9,4,9,3,9,1001,9,2,9,4,9,3,9,101,1,9,9,4,9,99,3,9,1002,9,2,9,4,9,3,9,1001,9,2,9,4,9,3,9,101,1,9,9,4,9,3,9,1002,9,2,9,4,9,3,9,1001,9,2,9,4,9,3,9,1001,9,2,9,4,9,3,9,1002,9,2,9,4,9,3,9,1001,9,2,9,4,9,3,9,101,2,9,9,4,9,3,9,102,2,9,9,4,9,99,3,9,101,1,9,9,4,9,3,9,1002,9,2,9,4,9,3,9,1001,9,1,9,4,9,3,9,1002,9,2,9,4,9,3,9,1001,9,1,9,4,9,3,9,1001,9,1,9,4,9,3,9,101,1,9,9,4,9,3,9,102,2,9,9,4,9,3,9,1001,9,1,9,4,9,3,9,1001,9,1,9,4,9,99,3,9,1001,9,1,9,4,9,3,9,1002,9,2,9,4,9,3,9,1001,9,2,9,4,9,3,9,1002,9,2,9,4,9,3,9,1001,9,1,9,4,9,3,9,101,2,9,9,4,9,3,9,102,2,9,9,4,9,3,9,101,2,9,9,4,9,3,9,101,1,9,9,4,9,3,9,1001,9,1,9,4,9,99,3,9,101,2,9,9,4,9,3,9,102,2,9,9,4,9,3,9,101,2,9,9,4,9,3,9,101,1,9,9,4,9,3,9,101,2,9,9,4,9,3,9,1002,9,2,9,4,9,3,9,102,2,9,9,4,9,3,9,1001,9,1,9,4,9,3,9,1001,9,1,9,4,9,3,9,101,2,9,9,4,9,99";
     let opcodes = process_input( input );
 
@@ -104,3 +127,12 @@ pub fn day_07( _args: Vec<String> ) {
     part_two( opcodes.clone() );
 }
 
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn cpu_passes_conformance() {
+        let report = super::conformance();
+        assert!( report.is_success(), "{}", report );
+    }
+}
@@ -23,6 +23,7 @@ pub trait Computer {
     fn display_std_out( &mut self, disp_std_out: bool );
     fn pause_execution_on_output( &mut self, pause_execution: bool );
     fn has_finished( &mut self ) -> bool;
+    fn get_memory( &self ) -> &HashMap< usize, i64 >;
 
     fn private_read_parameter( &mut self, parameter_mode: Option<i64> ) -> i64;
     fn read_digit( &mut self, digit: i64, place: u32 ) -> Option< i64 >;
@@ -81,7 +82,7 @@ impl Computer for CPU {
         let first_val  : i64 = self.private_read_parameter( param_one_mode );
         let second_val : i64 = self.private_read_parameter( param_two_mode );
 
-        let output = first_val + second_val;
+        let output = first_val.checked_add( second_val ).unwrap_or_else( || panic!( "arithmetic overflow in instruction at idx {}", self.idx - 3 ) );
 
         // write into memory
         let output_save = self.memory[&(self.idx)] as usize;
@@ -94,7 +95,7 @@ impl Computer for CPU {
         let first_val  : i64 = self.private_read_parameter( param_one_mode );
         let second_val : i64 = self.private_read_parameter( param_two_mode );
 
-        let output = first_val * second_val;
+        let output = first_val.checked_mul( second_val ).unwrap_or_else( || panic!( "arithmetic overflow in instruction at idx {}", self.idx - 3 ) );
 
         // write into memory
         let output_save = self.memory[&(self.idx)] as usize;
@@ -242,4 +243,8 @@ impl Computer for CPU {
     fn has_finished( &mut self ) -> bool {
         return self.finished;
     }
+
+    fn get_memory( &self ) -> &HashMap< usize, i64 > {
+        return &self.memory;
+    }
 }
\ No newline at end of file
@@ -24,6 +24,7 @@ pub trait Computer {
     fn display_std_out( &mut self, disp_std_out: bool );
     fn pause_execution_on_output( &mut self, pause_execution: bool );
     fn has_finished( &mut self ) -> bool;
+    fn get_memory( &self ) -> &HashMap< usize, i64 >;
 
     fn private_read_parameter( &mut self, parameter_mode: Option<i64> ) -> i64;
     fn private_write_parameter( &mut self, parameter_mode: Option<i64>, output: i64 );
@@ -88,7 +89,7 @@ impl Computer for CPU {
         let first_val  : i64 = self.private_read_parameter( param_one_mode );
         let second_val : i64 = self.private_read_parameter( param_two_mode );
 
-        let output = first_val + second_val;
+        let output = first_val.checked_add( second_val ).unwrap_or_else( || panic!( "arithmetic overflow in instruction at idx {}", self.idx - 3 ) );
 
         // write into memory
         self.private_write_parameter( param_three_mode, output );
@@ -98,7 +99,7 @@ impl Computer for CPU {
         let first_val  : i64 = self.private_read_parameter( param_one_mode );
         let second_val : i64 = self.private_read_parameter( param_two_mode );
 
-        let output = first_val * second_val;
+        let output = first_val.checked_mul( second_val ).unwrap_or_else( || panic!( "arithmetic overflow in instruction at idx {}", self.idx - 3 ) );
 
         // write into memory
         self.private_write_parameter( param_three_mode, output );
@@ -176,7 +177,7 @@ impl Computer for CPU {
 
     fn add_relative_offset( &mut self, param_one_mode: Option<i64>, _: Option<i64>, _: Option<i64> ) {
         let offset = self.private_read_parameter( param_one_mode );
-        self.relative_offset += offset;
+        self.relative_offset = self.relative_offset.checked_add( offset ).unwrap_or_else( || panic!( "arithmetic overflow in instruction at idx {}", self.idx - 2 ) );
     }
 
     fn exit( &mut self, _: Option<i64>, _: Option<i64>, _: Option<i64> ) {
@@ -206,8 +207,8 @@ impl Computer for CPU {
 
         match parameter_mode {
             Some( 1 ) => idx = self.idx,
-            Some( 2 ) => idx = (self.private_check_memory(self.idx) + self.relative_offset) as usize,
-            _ => idx = self.private_check_memory(self.idx) as usize
+            Some( 2 ) => idx = address( self.private_check_memory(self.idx), self.relative_offset, self.idx ),
+            _ => idx = address( self.private_check_memory(self.idx), 0, self.idx )
         }
 
         self.idx += 1;
@@ -221,11 +222,11 @@ impl Computer for CPU {
                 self.idx = self.memory[&(self.idx)] as usize;
             },
             Some( 2 ) => {
-                let sec_val_pos = (self.memory[&(self.idx)] + self.relative_offset) as usize;
+                let sec_val_pos = address( self.memory[&(self.idx)], self.relative_offset, self.idx );
                 self.idx = self.memory[&sec_val_pos] as usize;
             }
             _ => {
-                let sec_val_pos = self.memory[&(self.idx)] as usize;
+                let sec_val_pos = address( self.memory[&(self.idx)], 0, self.idx );
                 self.idx = self.memory[&sec_val_pos] as usize;
             }
         }
@@ -245,14 +246,14 @@ impl Computer for CPU {
     }
 
     fn private_write_parameter( &mut self, parameter_mode: Option<i64>, output: i64 ) {
-        let output_save: i64;
+        let output_save: usize;
         
         match parameter_mode {
-            Some( 2 ) => output_save = self.private_check_memory(self.idx) + self.relative_offset,
-            _ => output_save = self.private_check_memory(self.idx)
+            Some( 2 ) => output_save = address( self.private_check_memory(self.idx), self.relative_offset, self.idx ),
+            _ => output_save = address( self.private_check_memory(self.idx), 0, self.idx )
         }
 
-        self.memory.insert( output_save as usize, output );
+        self.memory.insert( output_save, output );
         self.idx += 1;
     }
 
@@ -263,4 +264,16 @@ impl Computer for CPU {
     fn has_finished( &mut self ) -> bool {
         return self.finished;
     }
-}
\ No newline at end of file
+
+    fn get_memory( &self ) -> &HashMap< usize, i64 > {
+        return &self.memory;
+    }
+}
+
+// operands are never meant to overflow or point below zero, stop rather than wrap round
+fn address( value: i64, offset: i64, operand_idx: usize ) -> usize {
+    return match value.checked_add( offset ) {
+        Some( address ) if address >= 0 => address as usize,
+        _ => panic!( "bad address {} + {} in operand at idx {}", value, offset, operand_idx )
+    };
+}
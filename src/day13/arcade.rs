@@ -0,0 +1,352 @@
+use std::cell::{
+    Cell,
+    RefCell
+};
+use std::collections::{
+    BTreeMap,
+    HashMap
+};
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::process::{
+    Command,
+    Stdio
+};
+use std::rc::Rc;
+
+use num_traits::FromPrimitive;
+
+use crate::intcode::cpu::{
+    CPU,
+    Computer,
+    Producer
+};
+use crate::intcode::devices::{
+    ClosureConsumer,
+    ClosureProducer,
+    FileProducer
+};
+
+use super::{
+    Coordinate,
+    TILE_ID,
+    print_board
+};
+
+// how long to wait for a key before the joystick goes back to neutral, in tenths of a second
+const FRAME_TENTHS: u32 = 1;
+
+/// The screen as the program draws it, three outputs per tile (x = -1, y = 0 sets the score).
+/// The ball and paddle are tracked as they are drawn so nobody has to search the board.
+#[derive(Default)]
+pub struct Arcade {
+    board: BTreeMap<Coordinate, TILE_ID>,
+    score: i64,
+    pending: Vec<i64>,
+    ball: Option<Coordinate>,
+    previous_ball: Option<Coordinate>,
+    paddle: Option<Coordinate>,
+    width: i64,
+    error: Option<String>
+}
+
+impl Arcade {
+    pub fn load_value( &mut self, value: i64 ) {
+        self.pending.push( value );
+
+        if self.pending.len() < 3 {
+            return;
+        }
+
+        let ( x, y, value ) = ( self.pending[0], self.pending[1], self.pending[2] );
+        self.pending.clear();
+
+        if x == -1 && y == 0 {
+            self.score = value;
+            return;
+        }
+
+        let coord = Coordinate { x: x, y: y };
+
+        // the program is not the puzzle's game, stop drawing rather than guess
+        let tile: TILE_ID = match FromPrimitive::from_i64( value ) {
+            Some( tile ) => tile,
+            None => {
+                self.error = Some( format!( "unknown tile id {} at {}, {}", value, x, y ) );
+                return;
+            }
+        };
+
+        match tile {
+            TILE_ID::BALL => {
+                self.previous_ball = self.ball;
+                self.ball = Some( coord );
+            },
+            TILE_ID::HORIZONTAL_PADDLE => self.paddle = Some( coord ),
+            TILE_ID::WALL => self.width = i64::max( self.width, x + 1 ),
+            _ => {}
+        }
+
+        self.board.insert( coord, tile );
+    }
+
+    pub fn board( &self ) -> &BTreeMap<Coordinate, TILE_ID> {
+        return &self.board;
+    }
+
+    pub fn score( &self ) -> i64 {
+        return self.score;
+    }
+
+    pub fn ball( &self ) -> Option<Coordinate> {
+        return self.ball;
+    }
+
+    // where the ball was the frame before, to tell which way it is going
+    pub fn previous_ball( &self ) -> Option<Coordinate> {
+        return self.previous_ball;
+    }
+
+    pub fn paddle( &self ) -> Option<Coordinate> {
+        return self.paddle;
+    }
+
+    // the walls are at x = 0 and x = width - 1
+    pub fn width( &self ) -> i64 {
+        return self.width;
+    }
+
+    // set when the program drew something that is not a tile, the game stops there
+    pub fn error( &self ) -> Option<&String> {
+        return self.error.as_ref();
+    }
+
+    pub fn blocks( &self ) -> usize {
+        return self.board.values().filter( |v| matches!( v, TILE_ID::BLOCK ) ).count();
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GameResult {
+    pub score: i64,
+    pub blocks: usize,
+    pub frames: usize
+}
+
+/// Moves the joystick once a frame: -1 left, 0 neutral, 1 right. None stops the game there.
+pub trait Strategy {
+    fn joystick( &mut self, arcade: &Arcade ) -> Option<i64>;
+}
+
+// steer the paddle under x
+fn towards( arcade: &Arcade, x: i64 ) -> i64 {
+    return match arcade.paddle() {
+        Some( paddle ) => ( x - paddle.x ).signum(),
+        None => 0
+    };
+}
+
+/// Keep the paddle under the ball.
+pub struct FollowBall {}
+
+impl Strategy for FollowBall {
+    fn joystick( &mut self, arcade: &Arcade ) -> Option<i64> {
+        return Some( arcade.ball().map( |ball| towards( arcade, ball.x ) ).unwrap_or( 0 ) );
+    }
+}
+
+/// Work out where a falling ball will reach the paddle's row, bouncing off the side walls,
+/// and wait there. Blocks in the way can still deflect it, so this re-plans every frame.
+pub struct PredictLanding {}
+
+impl Strategy for PredictLanding {
+    fn joystick( &mut self, arcade: &Arcade ) -> Option<i64> {
+        let ( ball, previous, paddle ) = match ( arcade.ball(), arcade.previous_ball(), arcade.paddle() ) {
+            ( Some( b ), Some( p ), Some( q ) ) => ( b, p, q ),
+            ( Some( b ), _, _ ) => return Some( towards( arcade, b.x ) ),
+            _ => return Some( 0 )
+        };
+
+        let ( dx, dy ) = ( ball.x - previous.x, ball.y - previous.y );
+
+        if dy <= 0 || dx == 0 {
+            return Some( towards( arcade, ball.x ) );
+        }
+
+        // unfold the bounces: the ball moves freely between x = 1 and x = width - 2
+        let span = i64::max( arcade.width() - 3, 1 );
+        let mut x = ( ball.x - 1 + dx * ( paddle.y - 1 - ball.y ) ).rem_euclid( 2 * span );
+
+        if x > span {
+            x = 2 * span - x;
+        }
+
+        return Some( towards( arcade, x + 1 ) );
+    }
+}
+
+/// Play back a saved joystick sequence, stopping when it runs out.
+pub struct Recorded {
+    moves: Vec<i64>,
+    next: usize
+}
+
+impl Recorded {
+    pub fn initialise( moves: Vec<i64> ) -> Self {
+        return Recorded { moves: moves, next: 0 };
+    }
+}
+
+impl Strategy for Recorded {
+    fn joystick( &mut self, _: &Arcade ) -> Option<i64> {
+        let value = self.moves.get( self.next ).cloned();
+        self.next += 1;
+        return value;
+    }
+}
+
+/// Run the game with a strategy on the joystick, drawing the board once a frame if asked to.
+/// Returns the arcade as it was left, and the score, blocks remaining and frames played, or
+/// an error if the program drew a tile id the arcade does not know.
+pub fn play<S: Strategy>( program: &HashMap<usize, i64>, strategy: &mut S, render: bool ) -> Result<( Arcade, GameResult ), String> {
+    let arcade = Rc::new( RefCell::new( Arcade::default() ) );
+    let joystick = Rc::new( Cell::new( 0 ) );
+
+    let producer_arcade = arcade.clone();
+    let producer = ClosureProducer::initialise( move |value| producer_arcade.borrow_mut().load_value( value ) );
+    let consumer_joystick = joystick.clone();
+    let consumer = ClosureConsumer::initialise( move || consumer_joystick.get() );
+
+    let mut cpu = CPU::initialise( program.clone(), consumer, producer );
+    let mut frames = 0;
+
+    cpu.display_std_out( false );
+
+    while !cpu.has_finished() && arcade.borrow().error().is_none() {
+        // ask the strategy before the input instruction runs, so stopping leaves the game untouched
+        if cpu.read_memory( cpu.get_instruction_pointer() ) % 100 == 3 {
+            let current = arcade.borrow();
+
+            if render {
+                print_board( current.board() );
+                println!( "score: {}", current.score() );
+            }
+
+            match strategy.joystick( &current ) {
+                Some( value ) => joystick.set( value ),
+                None => break
+            }
+
+            frames += 1;
+        }
+
+        cpu.execute_instruction();
+    }
+
+    drop( cpu );
+
+    let arcade = Rc::try_unwrap( arcade ).ok().expect( "arcade still borrowed" ).into_inner();
+
+    if let Some( error ) = arcade.error() {
+        return Err( error.clone() );
+    }
+
+    let result = GameResult { score: arcade.score(), blocks: arcade.blocks(), frames: frames };
+
+    return Ok( ( arcade, result ) );
+}
+
+// puts the terminal in raw mode (no line buffering, no echo, reads time out) for as long as
+// it lives, and puts the old settings back when dropped
+struct RawMode {
+    saved: String
+}
+
+fn stty( args: &[&str] ) -> io::Result<String> {
+    let output = Command::new( "stty" )
+        .args( args )
+        .stdin( Stdio::inherit() )
+        .output()?;
+
+    if !output.status.success() {
+        return Err( io::Error::new( io::ErrorKind::Other, "stty failed, is stdin a terminal?" ) );
+    }
+
+    return Ok( String::from_utf8_lossy( &output.stdout ).trim().to_string() );
+}
+
+impl RawMode {
+    fn enter() -> io::Result<Self> {
+        let saved = stty( &[ "-g" ] )?;
+        stty( &[ "-icanon", "-echo", "min", "0", "time", &FRAME_TENTHS.to_string() ] )?;
+
+        return Ok( RawMode { saved: saved } );
+    }
+}
+
+impl Drop for RawMode {
+    fn drop( &mut self ) {
+        stty( &[ &self.saved ] ).ok();
+    }
+}
+
+// a / left arrow, d / right arrow, anything else (or nothing) is neutral, q quits;
+// every move is kept so the game can be saved as a replay
+struct Keyboard {
+    moves: Vec<i64>
+}
+
+impl Strategy for Keyboard {
+    fn joystick( &mut self, _: &Arcade ) -> Option<i64> {
+        let mut buffer = [0u8; 8];
+        let read = io::stdin().read( &mut buffer ).unwrap_or( 0 );
+
+        let value = match &buffer[..read] {
+            [ b'q', .. ] => return None,
+            [ b'a', .. ] | [ 27, b'[', b'D', .. ] => -1,
+            [ b'd', .. ] | [ 27, b'[', b'C', .. ] => 1,
+            _ => 0
+        };
+
+        self.moves.push( value );
+        return Some( value );
+    }
+}
+
+/// Save a joystick sequence, one value per line.
+pub fn save_replay( path: &str, joystick: &[i64] ) -> io::Result<()> {
+    let mut producer = FileProducer::create( path )?;
+
+    for value in joystick.iter() {
+        producer.load_value( *value );
+    }
+
+    return producer.flush();
+}
+
+pub fn load_replay( path: &str ) -> io::Result<Vec<i64>> {
+    return fs::read_to_string( path )?
+        .split_whitespace()
+        .map( |v| v.parse().map_err( |_| io::Error::new( io::ErrorKind::InvalidData, format!( "'{}' is not a joystick value", v ) ) ) )
+        .collect();
+}
+
+/// Play the game from the keyboard, drawing the board before every move. The moves are saved
+/// to `replay_path` when the game ends or q is pressed, `Recorded` plays them back.
+pub fn play_interactive( program: &HashMap<usize, i64>, replay_path: &str ) -> io::Result<GameResult> {
+    let mut keyboard = Keyboard { moves: Vec::new() };
+
+    // every frame clears the screen, so the keys are shown once and the game waits to start
+    println!( "a / d or arrows to move, q to quit. Press enter to start" );
+    io::stdin().read_line( &mut String::new() )?;
+
+    let result = {
+        let _raw = RawMode::enter()?;
+        play( program, &mut keyboard, true ).map_err( |e| io::Error::new( io::ErrorKind::InvalidData, e ) )?.1
+    };
+
+    save_replay( replay_path, &keyboard.moves )?;
+
+    return Ok( result );
+}
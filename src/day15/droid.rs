@@ -0,0 +1,109 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use num_traits::FromPrimitive;
+
+use crate::intcode::cpu::{
+    CPU,
+    Computer
+};
+use crate::intcode::devices::{
+    ClosureConsumer,
+    VecProducer
+};
+
+use super::{
+    Object,
+    get_coordinate_update
+};
+use super::ship::{
+    DIRECTIONS,
+    ShipMap,
+    opposite
+};
+
+/// One repair droid on one machine. Every move is sent to the program and its status read back.
+pub struct Droid {
+    cpu: CPU<ClosureConsumer<Box<dyn FnMut() -> i64>>, VecProducer>,
+    next_move: Rc<Cell<i64>>,
+    moves: usize
+}
+
+impl Droid {
+    pub fn initialise( program: &HashMap<usize, i64> ) -> Self {
+        let next_move = Rc::new( Cell::new( 0 ) );
+        let consumer_move = next_move.clone();
+        let consumer: Box<dyn FnMut() -> i64> = Box::new( move || consumer_move.get() );
+
+        let mut cpu = CPU::initialise( program.clone(), ClosureConsumer::initialise( consumer ), VecProducer::initialise() );
+        cpu.display_std_out( false );
+        cpu.pause_execution_on_output( true );
+
+        return Droid { cpu: cpu, next_move: next_move, moves: 0 };
+    }
+
+    // what is in that direction, the droid only moves if it is not a wall
+    pub fn step( &mut self, direction: i64 ) -> Object {
+        self.next_move.set( direction );
+        self.moves += 1;
+        self.cpu.execute_instructions();
+
+        let status = self.cpu.get_output_value().expect( "the droid did not report back" );
+
+        return FromPrimitive::from_i64( status ).expect( "unknown status from the droid" );
+    }
+
+    pub fn moves( &self ) -> usize {
+        return self.moves;
+    }
+}
+
+/// Maps the whole ship with a single droid. It walks depth first into every square it has not
+/// seen and, once a square has nothing new around it, goes back the way it came by sending the
+/// opposite move. Each square is entered once and left once, each wall bumped into once, so the
+/// number of moves is linear in the size of the ship.
+pub fn explore( program: &HashMap<usize, i64> ) -> ( ShipMap, usize ) {
+    let mut droid = Droid::initialise( program );
+    let mut map = ShipMap::default();
+    let mut position = map.start;
+    // the moves that led to the current square, undone in reverse to backtrack
+    let mut trail: Vec<i64> = Vec::new();
+
+    map.tiles.insert( position, Object::Floor );
+
+    loop {
+        let unexplored = DIRECTIONS.iter()
+            .find( |d| !map.tiles.contains_key( &get_coordinate_update( position, **d ) ) )
+            .cloned();
+
+        match unexplored {
+            Some( direction ) => {
+                let next = get_coordinate_update( position, direction );
+                let object = droid.step( direction );
+
+                map.tiles.insert( next, object );
+
+                if object == Object::OxygenSystem {
+                    map.oxygen = Some( next );
+                }
+
+                if object != Object::Wall {
+                    position = next;
+                    trail.push( direction );
+                }
+            },
+            None => {
+                let direction = match trail.pop() {
+                    Some( direction ) => opposite( direction ),
+                    None => break
+                };
+
+                droid.step( direction );
+                position = get_coordinate_update( position, direction );
+            }
+        }
+    }
+
+    return ( map, droid.moves() );
+}
@@ -0,0 +1,290 @@
+use std::collections::{
+    HashMap,
+    HashSet,
+    VecDeque
+};
+use std::collections::hash_map::Entry;
+use std::fs;
+use std::io;
+
+use super::{
+    Coordinate,
+    Object,
+    get_coordinate_update
+};
+
+pub const DIRECTIONS: [i64; 4] = [ 1, 2, 3, 4 ];
+
+// north <-> south, west <-> east
+pub fn opposite( direction: i64 ) -> i64 {
+    match direction {
+        1 => 2,
+        2 => 1,
+        3 => 4,
+        4 => 3,
+        _ => panic!( "unknown direction found" )
+    }
+}
+
+/// Everything the droid has seen, keyed by position, and where it started.
+#[derive(Clone, Debug, Default)]
+pub struct ShipMap {
+    pub tiles: HashMap<Coordinate, Object>,
+    pub start: Coordinate,
+    pub oxygen: Option<Coordinate>
+}
+
+/// The answers as data: the moves from the start to the oxygen system and how many minutes
+/// the oxygen takes to reach every open square.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OxygenReport {
+    pub path: Vec<i64>,
+    pub fill_time: usize
+}
+
+impl ShipMap {
+    pub fn is_open( &self, coordinate: &Coordinate ) -> bool {
+        return matches!( self.tiles.get( coordinate ), Some( Object::Floor ) | Some( Object::OxygenSystem ) );
+    }
+
+    // open squares next to this one, with the move that gets there
+    pub fn neighbours( &self, coordinate: Coordinate ) -> Vec<( i64, Coordinate )> {
+        return DIRECTIONS.iter()
+            .map( |d| ( *d, get_coordinate_update( coordinate, *d ) ) )
+            .filter( |( _, c )| self.is_open( c ) )
+            .collect();
+    }
+
+    /// Breadth first from `source` over the open squares: the distance to every square
+    /// reached and the move that first got there, so paths can be walked back.
+    pub fn distances( &self, source: Coordinate ) -> HashMap<Coordinate, ( usize, i64 )> {
+        let mut reached: HashMap<Coordinate, ( usize, i64 )> = HashMap::new();
+        let mut queue: VecDeque<Coordinate> = VecDeque::new();
+
+        reached.insert( source, ( 0, 0 ) );
+        queue.push_back( source );
+
+        while let Some( current ) = queue.pop_front() {
+            let distance = reached[&current].0;
+
+            for ( direction, next ) in self.neighbours( current ) {
+                if let Entry::Vacant( entry ) = reached.entry( next ) {
+                    entry.insert( ( distance + 1, direction ) );
+                    queue.push_back( next );
+                }
+            }
+        }
+
+        return reached;
+    }
+
+    pub fn shortest_path( &self, from: Coordinate, to: Coordinate ) -> Option<Vec<i64>> {
+        let reached = self.distances( from );
+        let mut path = Vec::new();
+        let mut current = to;
+
+        reached.get( &to )?;
+
+        while current != from {
+            let direction = reached[&current].1;
+            path.push( direction );
+            current = get_coordinate_update( current, opposite( direction ) );
+        }
+
+        path.reverse();
+
+        return Some( path );
+    }
+
+    // minutes for oxygen let out at `source` to fill every open square it can reach
+    pub fn fill_time( &self, source: Coordinate ) -> usize {
+        return self.distances( source ).values().map( |( d, _ )| *d ).max().unwrap_or( 0 );
+    }
+
+    pub fn oxygen_report( &self ) -> Option<OxygenReport> {
+        let oxygen = self.oxygen?;
+
+        return Some( OxygenReport {
+            path: self.shortest_path( self.start, oxygen )?,
+            fill_time: self.fill_time( oxygen )
+        } );
+    }
+}
+
+/// The oxygen spreading minute by minute: `fronts[m]` are the squares that fill in minute m,
+/// `fronts[0]` being the oxygen system itself.
+#[derive(Clone, Debug, Default)]
+pub struct OxygenFill {
+    pub fronts: Vec<Vec<Coordinate>>
+}
+
+impl OxygenFill {
+    // minutes until the last square is filled
+    pub fn total_time( &self ) -> usize {
+        return self.fronts.len().saturating_sub( 1 );
+    }
+
+    pub fn filled_after( &self, minute: usize ) -> HashSet<Coordinate> {
+        return self.fronts.iter()
+            .take( minute + 1 )
+            .flat_map( |front| front.iter().cloned() )
+            .collect();
+    }
+}
+
+impl ShipMap {
+    /// Every minute the oxygen moves into the open squares next to the ones already filled.
+    pub fn simulate_fill( &self, source: Coordinate ) -> OxygenFill {
+        let mut filled: HashSet<Coordinate> = HashSet::new();
+        let mut fill = OxygenFill::default();
+        let mut front = vec![ source ];
+
+        filled.insert( source );
+
+        while !front.is_empty() {
+            let mut next_front = Vec::new();
+
+            for coordinate in front.iter() {
+                for ( _, next ) in self.neighbours( *coordinate ) {
+                    if filled.insert( next ) {
+                        next_front.push( next );
+                    }
+                }
+            }
+
+            fill.fronts.push( front );
+            front = next_front;
+        }
+
+        return fill;
+    }
+
+    /// The map as text: `#` wall, `.` open, `O` oxygen system, `D` where the droid started and
+    /// a space for anything never seen. Squares in `oxygen` are drawn as `O` too, which is how
+    /// the fill frames are drawn.
+    pub fn to_text_with( &self, oxygen: &HashSet<Coordinate> ) -> String {
+        let mut text = String::new();
+
+        if self.tiles.is_empty() {
+            return text;
+        }
+
+        let min_x = self.tiles.keys().map( |c| c.x ).min().unwrap();
+        let max_x = self.tiles.keys().map( |c| c.x ).max().unwrap();
+        let min_y = self.tiles.keys().map( |c| c.y ).min().unwrap();
+        let max_y = self.tiles.keys().map( |c| c.y ).max().unwrap();
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let coordinate = Coordinate { x: x, y: y };
+
+                let c = match self.tiles.get( &coordinate ) {
+                    _ if oxygen.contains( &coordinate ) => 'O',
+                    Some( Object::OxygenSystem ) => 'O',
+                    _ if coordinate == self.start => 'D',
+                    Some( Object::Wall ) => '#',
+                    Some( Object::Floor ) => '.',
+                    None => ' '
+                };
+
+                text.push( c );
+            }
+
+            text.push( '\n' );
+        }
+
+        return text;
+    }
+
+    pub fn to_text( &self ) -> String {
+        return self.to_text_with( &HashSet::new() );
+    }
+
+    /// Reads a map written by `to_text`. Coordinates are shifted so the droid starts at 0, 0
+    /// as it does when exploring; a map without a `D` starts at its top left corner.
+    pub fn from_text( text: &str ) -> Result<ShipMap, String> {
+        let mut map = ShipMap::default();
+        let mut start = None;
+
+        for ( y, line ) in text.lines().enumerate() {
+            for ( x, c ) in line.chars().enumerate() {
+                let coordinate = Coordinate { x: x as i64, y: y as i64 };
+
+                let object = match c {
+                    '#' => Object::Wall,
+                    '.' => Object::Floor,
+                    'O' => Object::OxygenSystem,
+                    'D' => {
+                        start = Some( coordinate );
+                        Object::Floor
+                    },
+                    ' ' => continue,
+                    _ => return Err( format!( "unexpected '{}' at line {}, column {}", c, y + 1, x + 1 ) )
+                };
+
+                map.tiles.insert( coordinate, object );
+            }
+        }
+
+        let offset = start.unwrap_or_default();
+
+        map.tiles = map.tiles.into_iter()
+            .map( |( c, o )| ( Coordinate { x: c.x - offset.x, y: c.y - offset.y }, o ) )
+            .collect();
+
+        let oxygen: Vec<Coordinate> = map.tiles.iter()
+            .filter( |( _, o )| **o == Object::OxygenSystem )
+            .map( |( c, _ )| *c )
+            .collect();
+
+        // the ship has a single oxygen system, more than one would make the fill ambiguous
+        if oxygen.len() > 1 {
+            return Err( format!( "found {} oxygen systems, expected at most one", oxygen.len() ) );
+        }
+
+        map.oxygen = oxygen.first().cloned();
+
+        return Ok( map );
+    }
+
+    pub fn save( &self, path: &str ) -> io::Result<()> {
+        return fs::write( path, self.to_text() );
+    }
+
+    pub fn load( path: &str ) -> io::Result<ShipMap> {
+        return ShipMap::from_text( &fs::read_to_string( path )? )
+            .map_err( |e| io::Error::new( io::ErrorKind::InvalidData, e ) );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = include_str!( "testMap.txt" );
+
+    #[test]
+    fn text_round_trip() {
+        let ship = ShipMap::from_text( MAP ).unwrap();
+
+        assert_eq!( ship.start, Coordinate::default() );
+        assert_eq!( ship.oxygen, Some( Coordinate { x: 1, y: 2 } ) );
+        assert_eq!( ship.to_text(), MAP );
+        assert_eq!( ShipMap::from_text( &ship.to_text() ).unwrap().tiles, ship.tiles );
+    }
+
+    #[test]
+    fn fill_time_from_the_map() {
+        let ship = ShipMap::from_text( MAP ).unwrap();
+        let fill = ship.simulate_fill( ship.oxygen.unwrap() );
+
+        assert_eq!( fill.total_time(), 4 );
+        assert_eq!( fill.filled_after( 1 ).len(), 3 );
+        assert_eq!( ship.oxygen_report().unwrap(), OxygenReport { path: vec![ 2, 2, 4 ], fill_time: 4 } );
+    }
+
+    #[test]
+    fn several_oxygen_systems_are_rejected() {
+        assert!( ShipMap::from_text( "#O.O#\n" ).is_err() );
+    }
+}
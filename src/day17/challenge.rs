@@ -1,14 +1,28 @@
 mod cpu;
+mod movement;
+mod scaffold;
 use self::cpu::{
     CPU,
     Computer
 };
+use self::movement::{
+    MEMORY_LIMIT,
+    compress
+};
+use self::scaffold::scaffold_path;
+use crate::intcode::conformance::Report;
 
 use std::collections::{
     HashMap,
     BTreeMap
 };
 
+use crate::intcode::service::{
+    Encoding,
+    ServiceOptions,
+    serve
+};
+
 const DATA: &str = include_str!( "./puzzleInput.txt" );
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
@@ -98,7 +112,7 @@ fn find_allignment_parameters( intersections: Vec< Coordinate > ) -> i64 {
     return sum;
 }
 
-fn part_one( mut cpu: CPU ) {
+fn part_one( mut cpu: CPU ) -> BTreeMap< Coordinate, u8 > {
     cpu.display_std_out( false );
     cpu.pause_execution_on_output( true );
 
@@ -106,10 +120,20 @@ fn part_one( mut cpu: CPU ) {
     let intersections = find_intersections( map.clone() );
     
     println!( "Part One: Allignment parameter = {}", find_allignment_parameters( intersections ) );
+
+    return map;
 }
 
-fn part_two( mut cpu: CPU ) {
-    let input = "A,B,A,C,A,B,C,A,B,C\nR,8,R,10,R,10\nR,4,R,8,R,10,R,12\nR,12,R,4,L,12,L,12\nn\n";
+fn part_two( mut cpu: CPU, path: &str ) {
+    let routines = match compress( path ) {
+        Some( routines ) => routines,
+        None => {
+            println!( "Part two: no main routine and movement functions of {} characters or less cover the path", MEMORY_LIMIT );
+            return;
+        }
+    };
+
+    let input = routines.to_input( false );
 
     cpu.display_std_out( false );
     cpu.pause_execution_on_output( false );
@@ -129,14 +153,42 @@ fn part_two( mut cpu: CPU ) {
     println!( "Part two result: {}", cpu.get_output_value() );
 }
 
+fn conformance() -> Report {
+    return crate::check_day_cpu!( CPU, 9 );
+}
+
 #[allow(dead_code)]
-pub fn day_17( _args: Vec<String> ) {
+pub fn day_17( args: Vec<String> ) {
+    // day_17 conformance: run the published example programs through this day's cpu
+    if args.get( 1 ).map( |a| a == "conformance" ).unwrap_or( false ) {
+        println!( "day 17 cpu\n{}", conformance() );
+        return;
+    }
+
     let opcodes = process_input( DATA );
     let cpu = CPU::initialise( opcodes.clone() );
     let mut opcodes_two = opcodes.clone();
     opcodes_two.insert( 0, 2 );
+
+    // day_17 serve <port | socket path>: type the movement routines in over a socket
+    if let ( Some( "serve" ), Some( address ) ) = ( args.get( 1 ).map( |a| a.as_str() ), args.get( 2 ) ) {
+        let options = ServiceOptions { encoding: Encoding::Ascii, instruction_limit: None };
+        serve( &opcodes_two, address, options ).expect( "failed to serve" );
+        return;
+    }
+
     let cpu_two = CPU::initialise( opcodes_two );
 
-    part_one( cpu );
-    part_two( cpu_two );
-}
\ No newline at end of file
+    let map = part_one( cpu );
+    let path = scaffold_path( &map ).expect( "the robot is not on the camera image" );
+
+    part_two( cpu_two, &path );
+}
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn cpu_passes_conformance() {
+        let report = super::conformance();
+        assert!( report.is_success(), "{}", report );
+    }
+}
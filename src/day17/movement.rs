@@ -0,0 +1,151 @@
+// each routine, commas included, has to fit in this many characters
+pub const MEMORY_LIMIT: usize = 20;
+// movement functions A, B and C
+const FUNCTIONS: usize = 3;
+// "A,A,...", as many calls as fit in memory
+const MAX_CALLS: usize = MEMORY_LIMIT.div_ceil( 2 );
+const NAMES: [char; FUNCTIONS] = [ 'A', 'B', 'C' ];
+
+/// A main routine calling movement functions A, B and C, which together walk a whole path.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Routines {
+    pub main: Vec<usize>,
+    pub functions: Vec<Vec<String>>
+}
+
+impl Routines {
+    pub fn main_routine( &self ) -> String {
+        return self.main.iter()
+            .map( |f| NAMES[*f].to_string() )
+            .collect::<Vec<String>>()
+            .join( "," );
+    }
+
+    // function A, B or C as typed in, an unused one is a single turn that is never called
+    pub fn function( &self, index: usize ) -> String {
+        return match self.functions.get( index ) {
+            Some( commands ) => commands.join( "," ),
+            None => "L".to_string()
+        };
+    }
+
+    /// Everything the robot asks for, one line each: the main routine, the three functions
+    /// and whether to show the video feed.
+    pub fn to_input( &self, video: bool ) -> String {
+        let mut input = format!( "{}\n", self.main_routine() );
+
+        for index in 0..FUNCTIONS {
+            input.push_str( &format!( "{}\n", self.function( index ) ) );
+        }
+
+        input.push_str( if video { "y\n" } else { "n\n" } );
+
+        return input;
+    }
+}
+
+// length of the commands once joined up with commas
+fn text_length( commands: &[&str] ) -> usize {
+    return commands.iter().map( |c| c.len() + 1 ).sum::<usize>().saturating_sub( 1 );
+}
+
+// depth first: at each point in the path either call a function that matches here or, while
+// there are names left, define a new one starting here (longest first)
+fn search<'a>( path: &[&'a str], at: usize, functions: &mut Vec<Vec<&'a str>>, main: &mut Vec<usize> ) -> bool {
+    if at == path.len() {
+        return true;
+    }
+
+    if main.len() == MAX_CALLS {
+        return false;
+    }
+
+    for index in 0..functions.len() {
+        if path[at..].starts_with( &functions[index] ) {
+            let length = functions[index].len();
+            main.push( index );
+
+            if search( path, at + length, functions, main ) {
+                return true;
+            }
+
+            main.pop();
+        }
+    }
+
+    if functions.len() == FUNCTIONS {
+        return false;
+    }
+
+    let longest = ( at + 1..=path.len() )
+        .take_while( |end| text_length( &path[at..*end] ) <= MEMORY_LIMIT )
+        .last()
+        .unwrap_or( at );
+
+    for end in ( at + 1..=longest ).rev() {
+        functions.push( path[at..end].to_vec() );
+        main.push( functions.len() - 1 );
+
+        if search( path, end, functions, main ) {
+            return true;
+        }
+
+        main.pop();
+        functions.pop();
+    }
+
+    return false;
+}
+
+/// Splits a path like "R,8,R,10,..." into a main routine and up to three movement functions,
+/// each no longer than `MEMORY_LIMIT` characters. Every way of cutting the path up is tried,
+/// a function may end between a turn and its distance. None if nothing fits.
+pub fn compress( path: &str ) -> Option<Routines> {
+    let commands: Vec<&str> = path.trim().split( "," ).filter( |c| !c.is_empty() ).collect();
+    let mut functions: Vec<Vec<&str>> = Vec::new();
+    let mut main: Vec<usize> = Vec::new();
+
+    if !search( &commands, 0, &mut functions, &mut main ) {
+        return None;
+    }
+
+    return Some( Routines {
+        main: main,
+        functions: functions.iter()
+            .map( |f| f.iter().map( |c| c.to_string() ).collect() )
+            .collect()
+    } );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the commands the main routine walks when every call is replaced by its function
+    fn expand( routines: &Routines ) -> String {
+        return routines.main.iter()
+            .map( |f| routines.function( *f ) )
+            .collect::<Vec<String>>()
+            .join( "," );
+    }
+
+    #[test]
+    fn compressed_routines_walk_the_path() {
+        let path = "R,8,R,8,R,4,R,4,R,8,L,6,L,2,R,4,R,4,R,8,R,8,R,8,L,6,L,2";
+        let routines = compress( path ).unwrap();
+
+        assert_eq!( expand( &routines ), path );
+        assert!( routines.main_routine().len() <= MEMORY_LIMIT );
+
+        for index in 0..FUNCTIONS {
+            assert!( routines.function( index ).len() <= MEMORY_LIMIT );
+        }
+    }
+
+    #[test]
+    fn path_that_does_not_fit_is_none() {
+        // every distance is different, so no function can be called more than once
+        let path = ( 1..=20 ).map( |d| format!( "R,{}", d ) ).collect::<Vec<String>>().join( "," );
+        assert_eq!( compress( &path ), None );
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+
+use super::Coordinate;
+
+const SCAFFOLD: u8 = b'#';
+
+/// Which way the robot is pointing, as a step on the camera image (y grows downwards).
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Facing {
+    pub dx: i64,
+    pub dy: i64
+}
+
+impl Facing {
+    fn from_tile( tile: u8 ) -> Option<Facing> {
+        return match tile {
+            b'^' => Some( Facing { dx: 0, dy: -1 } ),
+            b'v' => Some( Facing { dx: 0, dy: 1 } ),
+            b'<' => Some( Facing { dx: -1, dy: 0 } ),
+            b'>' => Some( Facing { dx: 1, dy: 0 } ),
+            _ => None
+        };
+    }
+
+    fn left( self ) -> Facing {
+        return Facing { dx: self.dy, dy: -self.dx };
+    }
+
+    fn right( self ) -> Facing {
+        return Facing { dx: -self.dy, dy: self.dx };
+    }
+
+    fn step( self, coordinate: Coordinate ) -> Coordinate {
+        return Coordinate { x: coordinate.x + self.dx, y: coordinate.y + self.dy };
+    }
+}
+
+// the robot is standing on scaffold too
+fn is_scaffold( map: &BTreeMap< Coordinate, u8 >, coordinate: Coordinate ) -> bool {
+    return match map.get( &coordinate ) {
+        Some( tile ) => *tile == SCAFFOLD || Facing::from_tile( *tile ).is_some(),
+        None => false
+    };
+}
+
+/// Where the robot is on the camera image and which way it faces. None if it is not on the
+/// image, or is tumbling through space (`X`).
+pub fn find_robot( map: &BTreeMap< Coordinate, u8 > ) -> Option<( Coordinate, Facing )> {
+    return map.iter()
+        .find_map( |( coordinate, tile )| Facing::from_tile( *tile ).map( |facing| ( *coordinate, facing ) ) );
+}
+
+/// Follows the scaffold from the robot to its far end and writes the walk down as commands,
+/// "R,8,R,10,...". The robot goes straight on through intersections and only turns where the
+/// scaffold does, so every piece of scaffold is visited.
+pub fn scaffold_path( map: &BTreeMap< Coordinate, u8 > ) -> Option<String> {
+    let ( mut position, mut facing ) = find_robot( map )?;
+    let mut commands: Vec<String> = Vec::new();
+
+    // facing away from the only way out, turn round first
+    if !is_scaffold( map, facing.step( position ) )
+        && !is_scaffold( map, facing.left().step( position ) )
+        && !is_scaffold( map, facing.right().step( position ) )
+        && is_scaffold( map, facing.left().left().step( position ) ) {
+        commands.push( "L".to_string() );
+        facing = facing.left();
+    }
+
+    loop {
+        let mut steps = 0;
+
+        while is_scaffold( map, facing.step( position ) ) {
+            position = facing.step( position );
+            steps += 1;
+        }
+
+        if steps > 0 {
+            commands.push( steps.to_string() );
+        }
+
+        if is_scaffold( map, facing.left().step( position ) ) {
+            commands.push( "L".to_string() );
+            facing = facing.left();
+        } else if is_scaffold( map, facing.right().step( position ) ) {
+            commands.push( "R".to_string() );
+            facing = facing.right();
+        } else {
+            break;
+        }
+    }
+
+    return Some( commands.join( "," ) );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the robot faces away from the scaffold at the left end, which crosses itself at 4, 3
+    const IMAGE: &str = "\
+....#####
+....#...#
+....#...#
+<########
+....#....
+....#....
+....#....";
+
+    fn camera_image( image: &str ) -> BTreeMap< Coordinate, u8 > {
+        let mut map: BTreeMap< Coordinate, u8 > = BTreeMap::new();
+
+        for ( y, line ) in image.lines().enumerate() {
+            for ( x, tile ) in line.bytes().enumerate() {
+                map.insert( Coordinate { x: x as i64, y: y as i64 }, tile );
+            }
+        }
+
+        return map;
+    }
+
+    #[test]
+    fn turns_round_and_goes_straight_through_intersections() {
+        assert_eq!( scaffold_path( &camera_image( IMAGE ) ), Some( "L,L,8,L,3,L,4,L,6".to_string() ) );
+    }
+
+    #[test]
+    fn no_robot_no_path() {
+        assert_eq!( scaffold_path( &camera_image( "..###.." ) ), None );
+    }
+}
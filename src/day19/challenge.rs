@@ -1,8 +1,17 @@
-mod cpu;
-use self::cpu::{
+use crate::intcode::batch::{
+    BatchJob,
+    run_batch,
+    default_workers
+};
+use crate::intcode::cpu::{
     CPU,
     Computer
 };
+use crate::intcode::devices::{
+    IteratorConsumer,
+    VecProducer
+};
+use crate::intcode::transpile::write_transpiled;
 
 use std::collections::{
     HashMap
@@ -13,30 +22,28 @@ const WIDTH: u64 = 50;
 const HEIGHT: u64 = 50;
 const SIZE: i64 = 100;
 
-fn find_start_end( cpu: CPU, l: u64, r: u64, y: u64 ) -> ( i64, i64 ) {
+fn in_beam( opcodes: &HashMap<usize, i64>, x: i64, y: i64 ) -> bool {
+    let mut cpu = CPU::initialise( opcodes.clone(), IteratorConsumer::initialise( vec![ x, y ] ), VecProducer::initialise() );
+    cpu.display_std_out( false );
+    cpu.execute_instructions();
+
+    return cpu.get_outputs_producer().take()[0] == 1;
+}
+
+fn find_start_end( opcodes: &HashMap<usize, i64>, l: u64, r: u64, y: u64 ) -> ( i64, i64 ) {
     // find start of aoe
     let mut left: i64 = -1;
     let mut right: i64 = -1;
 
     for x in l.. r {
-        let mut cpu_clone = cpu.clone();
-        cpu_clone.set_inputs( vec![ y as i64, x as i64 ] );
-        cpu_clone.execute_instructions();
-        let result = cpu_clone.get_output_value();  
-
-        if result == 1 {
+        if in_beam( opcodes, x as i64, y as i64 ) {
             left = x as i64;
             break;
         }
     }
 
     for x in ( l.. r ).rev() {
-        let mut cpu_clone = cpu.clone();
-        cpu_clone.set_inputs( vec![ y as i64, x as i64 ] );
-        cpu_clone.execute_instructions();
-        let result = cpu_clone.get_output_value();
-
-        if result == 1 {
+        if in_beam( opcodes, x as i64, y as i64 ) {
             right = x as i64;
             break;
         }
@@ -52,41 +59,33 @@ fn process_input( input: &str ) -> HashMap<usize, i64> {
         .collect();
 }
 
-fn part_one( cpu_in: CPU ) {
-    let mut cpu = cpu_in.clone();
-    cpu.display_std_out( false );
-    cpu.pause_execution_on_output( false );
-    let mut num_affected = 0;
-    let mut prev_coords: ( i64, i64 ) = ( 0, WIDTH as i64 );
-
-    for i in 0.. HEIGHT {
-        let coords = find_start_end( cpu.clone(), prev_coords.0 as u64, prev_coords.1 as u64, i );
-        
-        if coords.0 != -1 && coords.1 != -1 {
-            num_affected += coords.1 - coords.0 + 1;
+fn part_one( opcodes: &HashMap<usize, i64> ) {
+    // every point of the scan is an independent run, so hand them all to the batch runner
+    let jobs: Vec<BatchJob> = ( 0.. HEIGHT )
+        .flat_map( |y| ( 0.. WIDTH ).map( move |x| BatchJob::with_inputs( vec![ x as i64, y as i64 ] ) ) )
+        .collect();
 
-            if coords.0 - 10 < 0 {
-                prev_coords.0 = 0;
-            } else {
-                prev_coords.0 = coords.0 - 10;
-            }
+    let mut num_affected = 0;
 
-            if coords.1 + 10 > WIDTH as i64 {
-                prev_coords.1 = WIDTH as i64;
-            } else {
-                prev_coords.1 = coords.1 + 10;
+    for result in run_batch( opcodes, jobs, default_workers(), None ) {
+        match ( result.error, result.outputs.first() ) {
+            ( None, Some( 1 ) ) => num_affected += 1,
+            ( None, Some( _ ) ) => {},
+            ( Some( error ), _ ) => {
+                println!( "Part one: drone stopped: {}", error );
+                return;
+            },
+            ( None, None ) => {
+                println!( "Part one: drone halted without reporting" );
+                return;
             }
-
         }
     }
 
     println!( "Part one: num affected = {}", num_affected );
 }
 
-fn part_two( cpu_in: CPU ) {
-    let mut cpu = cpu_in.clone();
-    cpu.display_std_out( false );
-    cpu.pause_execution_on_output( false );
+fn part_two( opcodes: &HashMap<usize, i64> ) {
     let mut count = 0;
     let mut bottom_corner: ( i64, i64 ) = ( 0, 0 );
     let mut top_row: ( i64, i64 ) = ( 0, 0 );
@@ -96,7 +95,7 @@ fn part_two( cpu_in: CPU ) {
     let mut prev_coords_temp: ( i64, i64 ) = ( 0, WIDTH as i64 );
 
     while count != SIZE {
-        let coords = find_start_end( cpu.clone(), prev_coords.0 as u64, prev_coords.1 as u64, i );
+        let coords = find_start_end( opcodes, prev_coords.0 as u64, prev_coords.1 as u64, i );
 
         if coords.0 != -1 && coords.1 != -1 {
             if coords.0 - 5 < 0 {
@@ -139,10 +138,19 @@ fn part_two( cpu_in: CPU ) {
 }
 
 #[allow(dead_code)]
-pub fn day_19( _args: Vec< String > ) {
+pub fn day_19( args: Vec< String > ) {
     let opcodes = process_input( DATA );
-    let cpu = CPU::initialise( opcodes.clone() );
 
-    part_one( cpu.clone() );
-    part_two( cpu.clone() );
+    // day_19 transpile <file.rs>: write the beam tester out as a native rust program
+    if let ( Some( "transpile" ), Some( path ) ) = ( args.get( 1 ).map( |a| a.as_str() ), args.get( 2 ) ) {
+        match write_transpiled( &opcodes, path ) {
+            Ok( () ) => println!( "beam tester written to {}, build it with rustc --edition 2018 -O {}", path, path ),
+            Err( e ) => println!( "failed to write {}: {}", path, e )
+        }
+
+        return;
+    }
+
+    part_one( &opcodes );
+    part_two( &opcodes );
 }
@@ -3,11 +3,14 @@ use self::cpu::{
     CPU,
     Computer
 };
+use crate::intcode::conformance::Report;
 
 use std::collections::{
     HashMap
 };
 
+use crate::intcode::expect::run_script_file;
+
 const DATA: &str = include_str!( "./puzzleInput.txt" );
 
 fn process_input( input: &str ) -> HashMap<usize, i64> {
@@ -57,11 +60,40 @@ fn part_two( cpu: &mut CPU ) {
     springdroid( cpu, springscript );
 }
 
+fn conformance() -> Report {
+    return crate::check_day_cpu!( CPU, 9 );
+}
+
 #[allow(dead_code)]
-pub fn day_21( _args: Vec< String > ) {
+pub fn day_21( args: Vec< String > ) {
+    // day_21 conformance: run the published example programs through this day's cpu
+    if args.get( 1 ).map( |a| a == "conformance" ).unwrap_or( false ) {
+        println!( "day 21 cpu\n{}", conformance() );
+        return;
+    }
+
     let opcodes = process_input( DATA );
+
+    // day_21 script <file>: drive the springdroid from an expect script instead
+    if let ( Some( "script" ), Some( path ) ) = ( args.get( 1 ).map( |a| a.as_str() ), args.get( 2 ) ) {
+        match run_script_file( opcodes, path ).expect( "failed to read script" ) {
+            Ok( _ ) => {},
+            Err( e ) => println!( "script failed: {}", e )
+        }
+
+        return;
+    }
+
     let mut cpu = CPU::initialise( opcodes );
 
     part_one( &mut cpu.clone() );
     part_two( &mut cpu );
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn cpu_passes_conformance() {
+        let report = super::conformance();
+        assert!( report.is_success(), "{}", report );
+    }
+}
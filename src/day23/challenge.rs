@@ -1,16 +1,17 @@
-mod cpu;
-use self::cpu::{
+use crate::intcode::cpu::{
     CPU,
     Computer,
     Producer,
     Consumer
 };
+use crate::intcode::transpile::write_transpiled;
 
 use std::collections::LinkedList;
 use std::collections::HashMap;
 use std::sync::mpsc::Receiver;
 use std::sync::mpsc::SyncSender;
 use std::sync::mpsc::sync_channel;
+use std::time::Duration;
 
 // block on input and execute the other instructions
 
@@ -109,9 +110,25 @@ struct Network {
 
 trait NetworkControl {
     fn execute_loop( &mut self, nat: &mut NAT ) -> bool;
+    fn set_limits( &mut self, instruction_limit: Option<u64>, time_limit: Option<Duration> );
+    fn report_errors( &self );
 }
 
 impl NetworkControl for Network {
+    fn set_limits( &mut self, instruction_limit: Option<u64>, time_limit: Option<Duration> ) {
+        for cpu in self.network_interface_controllers.iter_mut() {
+            cpu.set_instruction_limit( instruction_limit );
+            cpu.set_time_limit( time_limit );
+        }
+    }
+
+    fn report_errors( &self ) {
+        for ( address, cpu ) in self.network_interface_controllers.iter().enumerate() {
+            if let Some( error ) = cpu.get_error() {
+                println!( "NIC {} stopped: {}", address, error );
+            }
+        }
+    }
 
     fn execute_loop( &mut self, nat: &mut NAT ) -> bool {
         for cpu in self.network_interface_controllers.iter_mut() {
@@ -124,8 +141,9 @@ impl NetworkControl for Network {
 
         nat.check_nat();
 
+        // a NIC over its limit has stopped without halting
         for cpu in self.network_interface_controllers.iter_mut() {
-            if !cpu.has_finished() {
+            if !cpu.has_finished() && cpu.get_error().is_none() {
                 return false;
             }
         }
@@ -235,15 +253,61 @@ fn initialise_network() -> ( Network, Option< NAT > ) {
     }, nat );
 }
 
-fn part_one() {
+fn part_one( instruction_limit: Option<u64>, time_limit: Option<Duration> ) {
     let ( mut network, nat_option ) = initialise_network();
     let mut nat = nat_option.unwrap();
 
+    network.set_limits( instruction_limit, time_limit );
+
     while !network.execute_loop( &mut nat ) {
     }
+
+    network.report_errors();
 }
 
+// the firmware never halts, this is plenty for the NAT to have sent both answers
+const DEFAULT_INSTRUCTION_LIMIT: u64 = 1_000_000;
+
+const USAGE: &str = "usage: day_23 [instruction limit per NIC] [seconds per NIC] | day_23 transpile <file.rs>";
+
+// optional arguments: instruction budget per NIC (DEFAULT_INSTRUCTION_LIMIT when left out),
+// then wall-clock seconds per NIC. The time limit is checked between instructions, which
+// works here because a NIC waiting for a packet never blocks: its consumer reads -1 straight
+// away and the NIC keeps running.
 #[allow(dead_code)]
-pub fn day_23( _: Vec<String> ) {
-    part_one();
+pub fn day_23( args: Vec<String> ) {
+    // day_23 transpile <file.rs>: write the NIC firmware out as a native rust program
+    if let ( Some( "transpile" ), Some( path ) ) = ( args.get( 1 ).map( |a| a.as_str() ), args.get( 2 ) ) {
+        match write_transpiled( &process_input( DATA ), path ) {
+            Ok( () ) => println!( "NIC firmware written to {}, build it with rustc --edition 2018 -O {}", path, path ),
+            Err( e ) => println!( "failed to write {}: {}", path, e )
+        }
+
+        return;
+    }
+
+    let instruction_limit: Option<u64> = match args.get( 1 ).map( |v| v.parse() ) {
+        None => Some( DEFAULT_INSTRUCTION_LIMIT ),
+        Some( Ok( limit ) ) => Some( limit ),
+        Some( Err( _ ) ) => {
+            println!( "{}", USAGE );
+            return;
+        }
+    };
+
+    let time_limit: Option<Duration> = match args.get( 2 ).map( |v| v.parse() ) {
+        None => None,
+        Some( Ok( seconds ) ) => Some( Duration::from_secs( seconds ) ),
+        Some( Err( _ ) ) => {
+            println!( "{}", USAGE );
+            return;
+        }
+    };
+
+    if args.len() > 3 {
+        println!( "{}", USAGE );
+        return;
+    }
+
+    part_one( instruction_limit, time_limit );
 }
\ No newline at end of file
@@ -1,70 +1,28 @@
-mod cpu;
-use self::cpu::{
+use crate::intcode::cpu::{
     CPU,
-    Computer,
-    Consumer,
-    Producer
+    Computer
+};
+use crate::intcode::devices::{
+    AsciiConsumer,
+    AsciiProducer
+};
+use crate::intcode::callstack::CallTracker;
+use crate::intcode::expect::run_script_file;
+use crate::intcode::gdbstub::GdbStub;
+use crate::intcode::service::{
+    Encoding,
+    ServiceOptions,
+    serve
+};
+use crate::intcode::strings::{
+    StringOptions,
+    extract_strings
 };
 
-use std::io::prelude::*;        
-use std::io;
-
-use std::collections::LinkedList;
 use std::collections::HashMap;
 
 const DATA: &str = include_str!( "./puzzleInput.txt" );
 
-struct StandardConsumer {
-    cache: LinkedList<i64>
-}
-
-trait SingleConsumer: Consumer {
-    fn initialise() -> Self;
-}
-
-impl Consumer for StandardConsumer {
-    fn get_value( &mut self ) -> i64 {
-        if let Some( v ) = self.cache.pop_front() {
-            return v;
-        }
-
-        let mut buff = String::new();
-        io::stdout().flush().ok().expect( "could not flush" );
-        io::stdin().read_line( &mut buff ).expect( "failed to read line" );
-
-        buff.chars()
-            .for_each( |c| { 
-                self.cache.push_back( c as u8 as i64 );
-            });
-
-        return self.cache.pop_front().unwrap();
-    }
-}
-
-impl SingleConsumer for StandardConsumer {
-    fn initialise() -> Self {
-        return StandardConsumer { cache: LinkedList::new() };
-    }
-}
-
-struct StandardProducer {}
-
-trait SingleProducer: Producer {
-    fn initialise() -> Self;
-}
-
-impl Producer for StandardProducer {
-    fn load_value( &mut self, value: i64 ) {
-        print!( "{}", value as u8 as char );
-    }
-}
-
-impl SingleProducer for StandardProducer {
-    fn initialise() -> Self {
-        return StandardProducer{};
-    }
-}
-
 fn process_input( input: &str ) -> HashMap<usize, i64> {
     return input.split( "," )
         .enumerate()
@@ -72,14 +30,76 @@ fn process_input( input: &str ) -> HashMap<usize, i64> {
         .collect();
 }
 
-pub fn day_25( _: Vec< String > ) {
-    let opcodes = process_input( DATA );
-    let consumer = StandardConsumer::initialise();
-    let producer = StandardProducer::initialise();
+const USAGE: &str = "usage: day_25 [command]
+  (nothing)        play the adventure
+  profile <file>   play, writing a folded stack profile of the session to the file
+  script <file>    play (part of) the adventure from an expect script
+  serve <address>  host the adventure for anyone who connects
+  strings [file]   dump the text tables, tracing a run with the commands in the file
+  debug <port>     play under gdb (`target remote :<port>` from gdb)";
+
+fn play( opcodes: HashMap<usize, i64>, profile_path: Option<&String> ) {
+    let consumer = AsciiConsumer::initialise();
+    let producer = AsciiProducer {};
     let mut cpu = CPU::initialise( opcodes, consumer, producer );
 
     cpu.display_std_out( false );
     cpu.pause_execution_on_output( false );
 
+    if profile_path.is_some() {
+        cpu.track_calls( Some( CallTracker::initialise( false ) ) );
+    }
+
     cpu.execute_instructions();
-}
\ No newline at end of file
+
+    if let Some( path ) = profile_path {
+        cpu.get_call_tracker().unwrap().write_folded( path ).expect( "failed to write profile" );
+    }
+}
+
+pub fn day_25( args: Vec< String > ) {
+    let opcodes = process_input( DATA );
+    let command = args.get( 1 ).map( |a| a.as_str() );
+    let argument = args.get( 2 );
+
+    match ( command, argument ) {
+        ( None, _ ) => play( opcodes, None ),
+        ( Some( "profile" ), Some( path ) ) => play( opcodes, Some( path ) ),
+        ( Some( "strings" ), _ ) => {
+            let commands = match argument {
+                Some( path ) => std::fs::read_to_string( path ).expect( "failed to read commands" ),
+                None => String::new()
+            };
+
+            for table in extract_strings( &opcodes, Some( &commands ), &StringOptions::default() ) {
+                println!( "{}", table );
+            }
+        },
+        ( Some( "script" ), Some( path ) ) => {
+            match run_script_file( opcodes, path ).expect( "failed to read script" ) {
+                Ok( _ ) => {},
+                Err( e ) => println!( "script failed: {}", e )
+            }
+        },
+        ( Some( "debug" ), Some( port ) ) => {
+            let port: u16 = match port.parse() {
+                Ok( port ) => port,
+                Err( _ ) => {
+                    println!( "{}", USAGE );
+                    return;
+                }
+            };
+
+            let mut cpu = CPU::initialise( opcodes, AsciiConsumer::initialise(), AsciiProducer {} );
+            cpu.display_std_out( false );
+
+            println!( "waiting for gdb on port {}", port );
+            GdbStub::initialise( cpu ).serve( port ).expect( "debug session failed" );
+        },
+        ( Some( "serve" ), Some( address ) ) => {
+            let options = ServiceOptions { encoding: Encoding::Ascii, instruction_limit: None };
+            serve( &opcodes, address, options ).expect( "failed to serve" );
+        },
+        _ => println!( "{}", USAGE )
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::{
+    HashMap,
+    VecDeque
+};
+use std::sync::{
+    Arc,
+    Mutex
+};
+use std::sync::mpsc::channel;
+use std::thread;
+
+use super::cpu::{
+    CPU,
+    Computer,
+    Consumer,
+    Producer
+};
+use super::error::ExecutionError;
+
+/// One run of the shared program: memory cells to overwrite before starting (e.g. day02's
+/// noun and verb) and the values handed out on opcode 3, in order.
+#[derive(Clone, Debug, Default)]
+pub struct BatchJob {
+    pub patches: Vec<( usize, i64 )>,
+    pub inputs: Vec<i64>
+}
+
+#[derive(Clone, Debug)]
+pub struct BatchResult {
+    pub outputs: Vec<i64>,
+    #[allow(dead_code)]
+    pub memory: HashMap<usize, i64>,
+    pub error: Option<ExecutionError>
+}
+
+struct BatchConsumer {
+    inputs: VecDeque<i64>
+}
+
+impl Consumer for BatchConsumer {
+    // run_job stops the machine before an input instruction it has nothing left for
+    fn get_value( &mut self ) -> i64 {
+        return self.inputs.pop_front().expect( "batch job ran out of inputs" );
+    }
+}
+
+// outputs are collected a step at a time instead
+struct BatchProducer {}
+
+impl Producer for BatchProducer {
+    fn load_value( &mut self, _: i64 ) {
+    }
+}
+
+impl BatchJob {
+    pub fn with_inputs( inputs: Vec<i64> ) -> Self {
+        return BatchJob { patches: Vec::new(), inputs: inputs };
+    }
+
+    #[allow(dead_code)]
+    pub fn with_patches( patches: Vec<( usize, i64 )> ) -> Self {
+        return BatchJob { patches: patches, inputs: Vec::new() };
+    }
+}
+
+pub fn default_workers() -> usize {
+    return thread::available_parallelism().map( |n| n.get() ).unwrap_or( 1 );
+}
+
+pub fn run_job( program: &HashMap<usize, i64>, job: &BatchJob, instruction_limit: Option<u64> ) -> BatchResult {
+    let mut memory = program.clone();
+
+    for ( idx, value ) in job.patches.iter() {
+        memory.insert( *idx, *value );
+    }
+
+    let consumer = BatchConsumer { inputs: job.inputs.iter().cloned().collect() };
+    let mut cpu = CPU::initialise( memory, consumer, BatchProducer {} );
+    let mut outputs: Vec<i64> = Vec::new();
+    let mut error: Option<ExecutionError> = None;
+
+    cpu.display_std_out( false );
+    cpu.set_instruction_limit( instruction_limit );
+
+    while !cpu.has_finished() && cpu.get_error().is_none() {
+        // a job that wants more input than it was given stops there, the rest of the batch goes on
+        let waiting = cpu.read_memory( cpu.get_instruction_pointer() ) % 100 == 3
+            && cpu.get_inputs_consumer().inputs.is_empty();
+
+        if waiting {
+            error = Some( ExecutionError::InputExhausted { idx: cpu.get_instruction_pointer(), steps: cpu.get_step_count() } );
+            break;
+        }
+
+        cpu.execute_instruction();
+
+        if let Some( value ) = cpu.get_output_value() {
+            outputs.push( value );
+        }
+    }
+
+    return BatchResult {
+        outputs: outputs,
+        memory: cpu.get_memory().clone(),
+        error: error.or( cpu.get_error() )
+    };
+}
+
+/// Runs every job against its own copy of the program on a pool of worker threads.
+/// Results come back in the same order as the jobs.
+pub fn run_batch( program: &HashMap<usize, i64>, jobs: Vec<BatchJob>, workers: usize, instruction_limit: Option<u64> ) -> Vec<BatchResult> {
+    let num_jobs = jobs.len();
+    let program = Arc::new( program.clone() );
+    let queue = Arc::new( Mutex::new( jobs.into_iter().enumerate().collect::<VecDeque<_>>() ) );
+    let ( tx, rx ) = channel();
+    let mut handles = Vec::new();
+
+    for _ in 0..usize::max( 1, usize::min( workers, num_jobs ) ) {
+        let program = Arc::clone( &program );
+        let queue = Arc::clone( &queue );
+        let tx = tx.clone();
+
+        handles.push( thread::spawn( move || {
+            loop {
+                let next = queue.lock().unwrap().pop_front();
+
+                match next {
+                    Some( ( idx, job ) ) => tx.send( ( idx, run_job( &program, &job, instruction_limit ) ) ).unwrap(),
+                    None => break
+                }
+            }
+        }));
+    }
+
+    drop( tx );
+
+    let mut results: Vec<Option<BatchResult>> = vec![None; num_jobs];
+
+    for ( idx, result ) in rx {
+        results[idx] = Some( result );
+    }
+
+    for handle in handles {
+        handle.join().expect( "batch worker panicked" );
+    }
+
+    return results.into_iter()
+        .map( |r| r.unwrap() )
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::process_input;
+
+    #[test]
+    fn job_out_of_input_does_not_stop_the_batch() {
+        // adds two inputs
+        let program = process_input( "3,11,3,12,1,11,12,13,4,13,99,0,0,0" );
+        let jobs = vec![ BatchJob::with_inputs( vec![ 1, 2 ] ), BatchJob::with_inputs( vec![ 5 ] ), BatchJob::with_inputs( vec![ 3, 4 ] ) ];
+        let results = run_batch( &program, jobs, 2, None );
+
+        assert_eq!( results[0].outputs, vec![ 3 ] );
+        assert_eq!( results[0].error, None );
+        assert_eq!( results[1].outputs, Vec::<i64>::new() );
+        assert_eq!( results[1].error, Some( ExecutionError::InputExhausted { idx: 2, steps: 1 } ) );
+        assert_eq!( results[2].outputs, vec![ 7 ] );
+    }
+}
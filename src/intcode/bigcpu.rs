@@ -0,0 +1,158 @@
+use std::collections::{
+    HashMap,
+    VecDeque
+};
+
+use num::{
+    BigInt,
+    ToPrimitive,
+    Zero,
+    One
+};
+
+/// Intcode machine whose memory cells are arbitrary precision integers, for programs whose
+/// values go past 64 bits. Addresses, opcodes and the relative base still have to fit in
+/// a usize / i64, everything else is unbounded. I/O is done through input and output queues.
+pub trait BigComputer {
+    fn initialise( memory: HashMap< usize, BigInt > ) -> Self;
+    fn execute_instruction( &mut self );
+    fn execute_instructions( &mut self );
+    fn set_inputs( &mut self, inputs: Vec<BigInt> );
+    fn get_outputs( &mut self ) -> Vec<BigInt>;
+    #[allow(dead_code)]
+    fn has_finished( &self ) -> bool;
+    fn get_memory( &self, idx: usize ) -> BigInt;
+    fn get_cells( &self ) -> &HashMap< usize, BigInt >;
+
+    fn private_address( &mut self, parameter_mode: i64 ) -> usize;
+    fn private_read_parameter( &mut self, parameter_mode: i64 ) -> BigInt;
+    fn private_write_parameter( &mut self, parameter_mode: i64, output: BigInt );
+}
+
+pub struct BigCPU {
+    memory: HashMap<usize, BigInt>,
+    idx: usize,
+    relative_offset: i64,
+    inputs: VecDeque<BigInt>,
+    outputs: Vec<BigInt>,
+    finished: bool
+}
+
+pub fn process_big_input( input: &str ) -> HashMap<usize, BigInt> {
+    return input.trim().split( "," )
+        .enumerate()
+        .map( |(i, v)| (i, v.trim().parse().unwrap()))
+        .collect();
+}
+
+fn bool_cell( value: bool ) -> BigInt {
+    return if value { BigInt::one() } else { BigInt::zero() };
+}
+
+impl BigComputer for BigCPU {
+    fn initialise( memory: HashMap< usize, BigInt > ) -> Self {
+        return BigCPU {
+            memory: memory,
+            idx: 0,
+            relative_offset: 0,
+            inputs: VecDeque::new(),
+            outputs: Vec::new(),
+            finished: false
+        };
+    }
+
+    fn execute_instructions( &mut self ) {
+        while !self.finished {
+            self.execute_instruction();
+        }
+    }
+
+    fn execute_instruction( &mut self ) {
+        let next_code = self.get_memory( self.idx ).to_i64().expect( "opcode does not fit in 64 bits" );
+        let modes = [ next_code / 100 % 10, next_code / 1_000 % 10, next_code / 10_000 % 10 ];
+        self.idx += 1;
+
+        match next_code % 100 {
+            1 | 2 | 7 | 8 => {
+                let first_val = self.private_read_parameter( modes[0] );
+                let second_val = self.private_read_parameter( modes[1] );
+
+                let output = match next_code % 100 {
+                    1 => first_val + second_val,
+                    2 => first_val * second_val,
+                    7 => bool_cell( first_val < second_val ),
+                    _ => bool_cell( first_val == second_val )
+                };
+
+                self.private_write_parameter( modes[2], output );
+            },
+            3 => {
+                let input = self.inputs.pop_front().expect( "no input available" );
+                self.private_write_parameter( modes[0], input );
+            },
+            4 => {
+                let value = self.private_read_parameter( modes[0] );
+                self.outputs.push( value );
+            },
+            5 | 6 => {
+                let condition = self.private_read_parameter( modes[0] );
+                let target = self.private_read_parameter( modes[1] );
+
+                if condition.is_zero() == ( next_code % 100 == 6 ) {
+                    self.idx = target.to_usize().expect( "jump target out of range" );
+                }
+            },
+            9 => {
+                let offset = self.private_read_parameter( modes[0] );
+                self.relative_offset += offset.to_i64().expect( "relative base out of range" );
+            },
+            99 => self.finished = true,
+            code => panic!( "unknown opcode {} at idx {}", code, self.idx - 1 )
+        }
+    }
+
+    fn set_inputs( &mut self, inputs: Vec<BigInt> ) {
+        self.inputs = inputs.into_iter().collect();
+    }
+
+    fn get_outputs( &mut self ) -> Vec<BigInt> {
+        return self.outputs.drain( .. ).collect();
+    }
+
+    fn has_finished( &self ) -> bool {
+        return self.finished;
+    }
+
+    fn get_memory( &self, idx: usize ) -> BigInt {
+        return self.memory.get( &idx ).cloned().unwrap_or_else( BigInt::zero );
+    }
+
+    fn get_cells( &self ) -> &HashMap< usize, BigInt > {
+        return &self.memory;
+    }
+
+    fn private_address( &mut self, parameter_mode: i64 ) -> usize {
+        let raw = self.get_memory( self.idx );
+        self.idx += 1;
+
+        return match parameter_mode {
+            2 => ( raw + self.relative_offset ).to_usize().expect( "address out of range" ),
+            _ => raw.to_usize().expect( "address out of range" )
+        };
+    }
+
+    fn private_read_parameter( &mut self, parameter_mode: i64 ) -> BigInt {
+        if parameter_mode == 1 {
+            self.idx += 1;
+            return self.get_memory( self.idx - 1 );
+        }
+
+        let idx = self.private_address( parameter_mode );
+        return self.get_memory( idx );
+    }
+
+    fn private_write_parameter( &mut self, parameter_mode: i64, output: BigInt ) {
+        let idx = self.private_address( parameter_mode );
+        self.memory.insert( idx, output );
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+use super::disasm::parameter_count;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub function: usize,
+    pub return_address: usize,
+    pub relative_offset: i64
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CallEvent {
+    Call { from: usize, function: usize, depth: usize },
+    Return { from: usize, to: usize, depth: usize }
+}
+
+/// Shadow call stack built from the calling convention intcode compilers use: the caller
+/// stores the return address at [rb+0] and then jumps, the callee moves the relative base
+/// for its frame and finally jumps back through the stored address.
+///
+/// - a taken jump whose fall through address is the value at [rb+0] is a call
+/// - a taken jump to the return address of a frame on the stack is a return to that frame
+///
+/// Every executed instruction is counted against the stack it ran in, which gives a
+/// folded stack profile that flame graph tools can read directly.
+#[derive(Clone, Debug, Default)]
+pub struct CallTracker {
+    stack: Vec<Frame>,
+    functions: Vec<usize>,
+    samples: HashMap<Vec<usize>, u64>,
+    events: Vec<CallEvent>,
+    keep_events: bool,
+    names: HashMap<usize, String>
+}
+
+impl CallTracker {
+    pub fn initialise( keep_events: bool ) -> Self {
+        return CallTracker {
+            stack: Vec::new(),
+            functions: Vec::new(),
+            samples: HashMap::new(),
+            events: Vec::new(),
+            keep_events: keep_events,
+            names: HashMap::new()
+        };
+    }
+
+    #[allow(dead_code)]
+    pub fn set_name( &mut self, function: usize, name: &str ) {
+        self.names.insert( function, name.to_string() );
+    }
+
+    /// Called once per executed instruction with the state after it ran. `return_slot` is
+    /// the value at [rb+0], `next_idx` the instruction pointer the CPU continues from.
+    pub fn step( &mut self, address: usize, opcode: i64, next_idx: usize, relative_offset: i64, return_slot: i64 ) {
+        match self.samples.get_mut( &self.functions[..] ) {
+            Some( count ) => *count += 1,
+            None => {
+                self.samples.insert( self.functions.clone(), 1 );
+            }
+        }
+
+        if opcode != 5 && opcode != 6 {
+            return;
+        }
+
+        let fall_through = address + parameter_count( opcode ).unwrap() + 1;
+
+        if next_idx == fall_through {
+            return;
+        }
+
+        if let Some( depth ) = self.stack.iter().rposition( |f| f.return_address == next_idx ) {
+            self.stack.truncate( depth );
+            self.functions.truncate( depth );
+
+            if self.keep_events {
+                self.events.push( CallEvent::Return { from: address, to: next_idx, depth: depth } );
+            }
+        } else if return_slot == fall_through as i64 {
+            self.stack.push( Frame { function: next_idx, return_address: fall_through, relative_offset: relative_offset } );
+            self.functions.push( next_idx );
+
+            if self.keep_events {
+                self.events.push( CallEvent::Call { from: address, function: next_idx, depth: self.stack.len() } );
+            }
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn current_stack( &self ) -> &Vec<Frame> {
+        return &self.stack;
+    }
+
+    #[allow(dead_code)]
+    pub fn events( &self ) -> &Vec<CallEvent> {
+        return &self.events;
+    }
+
+    pub fn function_name( &self, function: usize ) -> String {
+        return match self.names.get( &function ) {
+            Some( name ) => name.clone(),
+            None => format!( "fn_{}", function )
+        };
+    }
+
+    // instructions executed inside each function, not counting the functions it called
+    #[allow(dead_code)]
+    pub fn self_counts( &self ) -> HashMap<String, u64> {
+        let mut counts: HashMap<String, u64> = HashMap::new();
+
+        for ( stack, count ) in self.samples.iter() {
+            let name = match stack.last() {
+                Some( function ) => self.function_name( *function ),
+                None => "main".to_string()
+            };
+
+            *counts.entry( name ).or_insert( 0 ) += count;
+        }
+
+        return counts;
+    }
+
+    /// One line per distinct stack, "main;fn_a;fn_b count", sorted so the output is stable.
+    pub fn folded( &self ) -> String {
+        let mut lines: Vec<String> = self.samples.iter()
+            .map( |( stack, count )| {
+                let mut names = vec!["main".to_string()];
+                names.extend( stack.iter().map( |f| self.function_name( *f ) ) );
+
+                format!( "{} {}", names.join( ";" ), count )
+            })
+            .collect();
+
+        lines.sort();
+
+        return lines.join( "\n" ) + "\n";
+    }
+
+    pub fn write_folded( &self, path: &str ) -> io::Result<()> {
+        return fs::write( path, self.folded() );
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::panic;
+use std::vec;
+
+use num::{
+    BigInt,
+    ToPrimitive
+};
+
+use super::bigcpu::BigComputer;
+use super::cpu::Computer;
+use super::devices::{
+    IteratorConsumer,
+    VecProducer
+};
+use super::process_input;
+
+// long enough for every published example, short enough to catch an engine stuck in a loop
+const INSTRUCTION_LIMIT: u64 = 100_000;
+
+/// One of the published example programs with what it must do. `day` is the puzzle that
+/// introduced the instructions it needs, so an engine written for day 5 can skip day 9's
+/// relative base cases. `memory` is the expected start of memory once the program halts.
+#[derive(Clone, Debug)]
+pub struct Case {
+    pub name: String,
+    pub day: u32,
+    pub program: &'static str,
+    pub inputs: Vec<i64>,
+    pub outputs: Vec<i64>,
+    pub memory: Option<&'static str>
+}
+
+/// What an engine did with a case. Engines that cannot show their memory leave it as None
+/// and the memory check is skipped.
+#[derive(Clone, Debug, Default)]
+pub struct Run {
+    pub outputs: Vec<i64>,
+    pub memory: Option<HashMap<usize, i64>>
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    Failed( String ),
+    Skipped( String )
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    pub results: Vec<( String, Outcome )>
+}
+
+impl Report {
+    pub fn passed( &self ) -> usize {
+        return self.results.iter().filter( |( _, o )| *o == Outcome::Passed ).count();
+    }
+
+    pub fn failed( &self ) -> usize {
+        return self.results.iter().filter( |( _, o )| matches!( o, Outcome::Failed( _ ) ) ).count();
+    }
+
+    pub fn skipped( &self ) -> usize {
+        return self.results.iter().filter( |( _, o )| matches!( o, Outcome::Skipped( _ ) ) ).count();
+    }
+
+    #[allow(dead_code)]
+    pub fn is_success( &self ) -> bool {
+        return self.failed() == 0;
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        for ( name, outcome ) in self.results.iter() {
+            match outcome {
+                Outcome::Passed => writeln!( f, "pass  {}", name )?,
+                Outcome::Failed( reason ) => writeln!( f, "FAIL  {}: {}", name, reason )?,
+                Outcome::Skipped( reason ) => writeln!( f, "skip  {}: {}", name, reason )?
+            }
+        }
+
+        return write!( f, "{} passed, {} failed, {} skipped", self.passed(), self.failed(), self.skipped() );
+    }
+}
+
+fn case( name: &str, day: u32, program: &'static str, inputs: Vec<i64>, outputs: Vec<i64>, memory: Option<&'static str> ) -> Case {
+    return Case {
+        name: name.to_string(),
+        day: day,
+        program: program,
+        inputs: inputs,
+        outputs: outputs,
+        memory: memory
+    };
+}
+
+// one case per input, each paired with the output the program gives for it
+fn cases_for_inputs( name: &str, day: u32, program: &'static str, io: &[( i64, i64 )] ) -> Vec<Case> {
+    return io.iter()
+        .map( |( input, output )| case( &format!( "{} ({})", name, input ), day, program, vec![ *input ], vec![ *output ], None ) )
+        .collect();
+}
+
+/// Every example program from the puzzle texts of days 2, 5 and 9.
+pub fn cases() -> Vec<Case> {
+    let mut cases = vec![
+        case( "day02 add", 2, "1,0,0,0,99", vec![], vec![], Some( "2,0,0,0,99" ) ),
+        case( "day02 multiply", 2, "2,3,0,3,99", vec![], vec![], Some( "2,3,0,6,99" ) ),
+        case( "day02 multiply past the end", 2, "2,4,4,5,99,0", vec![], vec![], Some( "2,4,4,5,99,9801" ) ),
+        case( "day02 overwrite the halt", 2, "1,1,1,4,99,5,6,0,99", vec![], vec![], Some( "30,1,1,4,2,5,6,0,99" ) ),
+        case( "day02 worked example", 2, "1,9,10,3,2,3,11,0,99,30,40,50", vec![], vec![], Some( "3500,9,10,70,2,3,11,0,99,30,40,50" ) ),
+        case( "day05 echo", 5, "3,0,4,0,99", vec![ 42 ], vec![ 42 ], None ),
+        case( "day05 parameter modes", 5, "1002,4,3,4,33", vec![], vec![], Some( "1002,4,3,4,99" ) ),
+        case( "day05 negative values", 5, "1101,100,-1,4,0", vec![], vec![], Some( "1101,100,-1,4,99" ) ),
+        case( "day09 quine", 9, "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99", vec![],
+            vec![ 109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99 ], None ),
+        case( "day09 16 digit product", 9, "1102,34915192,34915192,7,4,7,99,0", vec![], vec![ 1219070632396864 ], None ),
+        case( "day09 large immediate", 9, "104,1125899906842624,99", vec![], vec![ 1125899906842624 ], None )
+    ];
+
+    cases.extend( cases_for_inputs( "day05 equals 8 position", 5, "3,9,8,9,10,9,4,9,99,-1,8", &[ ( 8, 1 ), ( 7, 0 ) ] ) );
+    cases.extend( cases_for_inputs( "day05 less than 8 position", 5, "3,9,7,9,10,9,4,9,99,-1,8", &[ ( 7, 1 ), ( 8, 0 ) ] ) );
+    cases.extend( cases_for_inputs( "day05 equals 8 immediate", 5, "3,3,1108,-1,8,3,4,3,99", &[ ( 8, 1 ), ( 9, 0 ) ] ) );
+    cases.extend( cases_for_inputs( "day05 less than 8 immediate", 5, "3,3,1107,-1,8,3,4,3,99", &[ ( 7, 1 ), ( 8, 0 ) ] ) );
+    cases.extend( cases_for_inputs( "day05 jump position", 5, "3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9", &[ ( 0, 0 ), ( 5, 1 ) ] ) );
+    cases.extend( cases_for_inputs( "day05 jump immediate", 5, "3,3,1105,-1,9,1101,0,0,12,4,12,99,1", &[ ( 0, 0 ), ( 5, 1 ) ] ) );
+    cases.extend( cases_for_inputs( "day05 compare with 8", 5,
+        "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99",
+        &[ ( 7, 999 ), ( 8, 1000 ), ( 9, 1001 ) ] ) );
+
+    return cases;
+}
+
+fn check_case( case: &Case, run: &Run ) -> Outcome {
+    if run.outputs != case.outputs {
+        return Outcome::Failed( format!( "expected outputs {:?}, got {:?}", case.outputs, run.outputs ) );
+    }
+
+    if let Some( expected ) = case.memory {
+        let memory = match run.memory.as_ref() {
+            Some( memory ) => memory,
+            None => return Outcome::Skipped( "engine does not expose its memory".to_string() )
+        };
+
+        let expected = process_input( expected );
+
+        for idx in 0..expected.len() {
+            let actual = memory.get( &idx ).cloned().unwrap_or( 0 );
+
+            if actual != expected[&idx] {
+                return Outcome::Failed( format!( "expected {} at address {}, found {}", expected[&idx], idx, actual ) );
+            }
+        }
+    }
+
+    return Outcome::Passed;
+}
+
+/// Runs the suite through any engine: `run` is given a case and runs its program with its
+/// inputs. Cases introduced after `max_day` are skipped, a panic counts as a failure.
+pub fn check_with<F: FnMut( &Case ) -> Run>( max_day: u32, mut run: F ) -> Report {
+    let mut report = Report::default();
+
+    for case in cases() {
+        if case.day > max_day {
+            report.results.push( ( case.name.clone(), Outcome::Skipped( format!( "needs day {} instructions", case.day ) ) ) );
+            continue;
+        }
+
+        let outcome = match panic::catch_unwind( panic::AssertUnwindSafe( || run( &case ) ) ) {
+            Ok( result ) => check_case( &case, &result ),
+            Err( _ ) => Outcome::Failed( "panicked".to_string() )
+        };
+
+        report.results.push( ( case.name.clone(), outcome ) );
+    }
+
+    return report;
+}
+
+/// Runs the suite against one of the per-day cpu copies: `check_day_cpu!( CPU, 9 )` with the
+/// day's `CPU` and `Computer` in scope, 9 being the last day whose instructions it knows.
+/// The copies predate the shared `Computer` trait and each declare their own with the same
+/// methods, so they are driven through a macro rather than a trait bound.
+#[macro_export]
+macro_rules! check_day_cpu {
+    ( $cpu:ty, $max_day:expr ) => {
+        $crate::intcode::conformance::check_with( $max_day, |case| {
+            let mut cpu: $cpu = Computer::initialise( $crate::intcode::process_input( case.program ) );
+            let mut outputs = Vec::new();
+
+            // set_inputs hands them out from the back
+            cpu.set_inputs( case.inputs.iter().rev().cloned().collect() );
+            cpu.display_std_out( false );
+            cpu.pause_execution_on_output( true );
+
+            while !cpu.has_finished() {
+                cpu.execute_instructions();
+
+                if !cpu.has_finished() {
+                    outputs.push( cpu.get_output_value() );
+                }
+            }
+
+            $crate::intcode::conformance::Run { outputs: outputs, memory: Some( cpu.get_memory().clone() ) }
+        } )
+    };
+}
+
+/// Runs the whole suite against an engine implementing the shared `Computer` trait.
+pub fn check_computer<C: Computer<IteratorConsumer<vec::IntoIter<i64>>, VecProducer>>() -> Report {
+    return check_with( u32::MAX, |case| {
+        let mut cpu: C = Computer::initialise(
+            process_input( case.program ),
+            IteratorConsumer::initialise( case.inputs.clone() ),
+            VecProducer::initialise()
+        );
+
+        cpu.display_std_out( false );
+        cpu.set_instruction_limit( Some( INSTRUCTION_LIMIT ) );
+
+        if let Err( error ) = cpu.try_execute_instructions() {
+            panic!( "{}", error );
+        }
+
+        return Run {
+            outputs: cpu.get_outputs_producer().take(),
+            memory: Some( cpu.get_memory().clone() )
+        };
+    } );
+}
+
+/// Runs the whole suite against an arbitrary precision engine. Cells are compared as i64, one
+/// that has grown past 64 bits shows up as a mismatch.
+pub fn check_big_computer<C: BigComputer>() -> Report {
+    return check_with( u32::MAX, |case| {
+        let mut cpu: C = BigComputer::initialise( process_input( case.program ).into_iter()
+            .map( |( idx, value )| ( idx, BigInt::from( value ) ) )
+            .collect() );
+
+        cpu.set_inputs( case.inputs.iter().map( |value| BigInt::from( *value ) ).collect() );
+        cpu.execute_instructions();
+
+        return Run {
+            outputs: cpu.get_outputs().iter().map( |value| value.to_i64().unwrap_or( i64::MIN ) ).collect(),
+            memory: Some( cpu.get_cells().iter()
+                .map( |( idx, value )| ( *idx, value.to_i64().unwrap_or( i64::MIN ) ) )
+                .collect() )
+        };
+    } );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::bigcpu::BigCPU;
+    use crate::intcode::cpu::CPU;
+
+    #[test]
+    fn shared_cpu_passes() {
+        let report = check_computer::<CPU<IteratorConsumer<vec::IntoIter<i64>>, VecProducer>>();
+        assert!( report.is_success(), "{}", report );
+        assert_eq!( report.skipped(), 0 );
+    }
+
+    #[test]
+    fn big_cpu_passes() {
+        let report = check_big_computer::<BigCPU>();
+        assert!( report.is_success(), "{}", report );
+        assert_eq!( report.skipped(), 0 );
+    }
+}
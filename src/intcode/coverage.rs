@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+use super::cpu::{
+    CPU,
+    Computer,
+    Consumer,
+    Producer
+};
+use super::devices::{
+    IteratorConsumer,
+    VecProducer
+};
+use super::disasm::{
+    Line,
+    find_code,
+    disassemble_with_code,
+    format_line
+};
+
+/// Hit counts per instruction address, merged over any number of runs. Run a CPU with
+/// `record_coverage( true )` then hand it over with `add_cpu`.
+#[derive(Clone, Debug, Default)]
+pub struct Coverage {
+    hits: HashMap<usize, u64>,
+    runs: usize
+}
+
+impl Coverage {
+    pub fn initialise() -> Self {
+        return Coverage { hits: HashMap::new(), runs: 0 };
+    }
+
+    pub fn add_run( &mut self, hits: &HashMap<usize, u64> ) {
+        for ( address, count ) in hits.iter() {
+            *self.hits.entry( *address ).or_insert( 0 ) += count;
+        }
+
+        self.runs += 1;
+    }
+
+    pub fn add_cpu<T: Consumer, U: Producer, C: Computer<T, U>>( &mut self, cpu: &C ) {
+        let hits = cpu.get_coverage().expect( "coverage was not recorded for this cpu" );
+        self.add_run( hits );
+    }
+
+    pub fn hits( &self, address: usize ) -> u64 {
+        return *self.hits.get( &address ).unwrap_or( &0 );
+    }
+
+    pub fn executed_addresses( &self ) -> Vec<usize> {
+        let mut addresses: Vec<usize> = self.hits.keys().cloned().collect();
+        addresses.sort();
+        return addresses;
+    }
+
+    /// Disassembly of the program with the hit count in front of every instruction.
+    /// Instructions that never ran are marked with "#####", data cells are left blank.
+    pub fn report( &self, program: &HashMap<usize, i64> ) -> String {
+        let code = find_code( program, &self.executed_addresses() );
+        let lines = disassemble_with_code( program, &code );
+        let mut output = String::new();
+        let mut total = 0;
+        let mut executed = 0;
+
+        for line in lines.iter() {
+            let prefix = match line {
+                Line::Code( instruction ) => {
+                    total += 1;
+
+                    match self.hits( instruction.address ) {
+                        0 => "#####".to_string(),
+                        count => {
+                            executed += 1;
+                            count.to_string()
+                        }
+                    }
+                },
+                Line::Data( _, _ ) => String::new()
+            };
+
+            output.push_str( &format!( "{:>10} {}\n", prefix, format_line( line ) ) );
+        }
+
+        let percentage = if total == 0 { 0.0 } else { 100.0 * executed as f64 / total as f64 };
+        let header = format!( "coverage: {} / {} instructions executed over {} runs ({:.1}%)\n", executed, total, self.runs, percentage );
+
+        return header + &output;
+    }
+
+    pub fn write_report( &self, program: &HashMap<usize, i64>, path: &str ) -> io::Result<()> {
+        return fs::write( path, self.report( program ) );
+    }
+}
+
+/// Runs the program once per set of inputs on the shared cpu and merges the hit counts,
+/// e.g. day 5's TEST with system ids 1 and 5.
+pub fn run_coverage( program: &HashMap<usize, i64>, input_sets: &[Vec<i64>] ) -> Coverage {
+    let mut coverage = Coverage::initialise();
+
+    for inputs in input_sets.iter() {
+        let mut cpu = CPU::initialise( program.clone(), IteratorConsumer::initialise( inputs.clone() ), VecProducer::initialise() );
+
+        cpu.display_std_out( false );
+        cpu.record_coverage( true );
+        cpu.execute_instructions();
+
+        coverage.add_cpu( &cpu );
+    }
+
+    return coverage;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::process_input;
+
+    // counts cell 20 down to 0 from the input, the output at 15 only runs for an input of 0
+    const COUNTDOWN: &str = "3,20,1006,20,15,1001,20,-1,20,1005,20,5,1105,1,17,104,-1,99";
+
+    #[test]
+    fn hit_counts_are_merged_over_runs() {
+        let program = process_input( COUNTDOWN );
+        let coverage = run_coverage( &program, &[ vec![ 3 ], vec![ 2 ] ] );
+
+        assert_eq!( coverage.hits( 0 ), 2 );
+        assert_eq!( coverage.hits( 5 ), 5 );
+        assert_eq!( coverage.hits( 9 ), 5 );
+        assert_eq!( coverage.hits( 15 ), 0 );
+        assert_eq!( coverage.executed_addresses(), vec![ 0, 2, 5, 9, 12, 17 ] );
+    }
+
+    #[test]
+    fn report_marks_instructions_that_never_ran() {
+        let program = process_input( COUNTDOWN );
+        let report = run_coverage( &program, &[ vec![ 3 ] ] ).report( &program );
+        let lines: Vec<&str> = report.lines().collect();
+
+        assert!( lines[0].starts_with( "coverage: 6 / 7 instructions executed over 1 runs" ), "{}", report );
+        assert_eq!( lines[3], "         3      5: add [20], -1, [20]" );
+        assert_eq!( lines[6], "     #####     15: out -1" );
+    }
+}
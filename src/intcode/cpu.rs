@@ -0,0 +1,903 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::time::{
+    Duration,
+    Instant
+};
+
+use super::callstack::CallTracker;
+use super::error::ExecutionError;
+use super::mmio::{
+    MemoryDevice,
+    MappedDevice
+};
+use super::observer::Observer;
+use super::reverse::{
+    UndoEntry,
+    UndoLog
+};
+
+pub type Instruction<T, U> = fn( &mut CPU<T, U>, Option<i64>, Option<i64>, Option<i64> );
+
+pub trait Computer< T: Consumer, U: Producer > {
+    fn initialise( memory: HashMap< usize, i64 >, _: T, _: U ) -> Self;
+    fn add( &mut self, _: Option<i64>, _: Option<i64>, _: Option<i64> ); 
+    fn multiply( &mut self, _: Option<i64>, _: Option<i64>, _: Option<i64> ); 
+    fn exit( &mut self, _: Option<i64>, _: Option<i64>, _: Option<i64> ); 
+    fn read( &mut self, _: Option<i64>, _: Option<i64>, _: Option<i64> ); 
+    fn write( &mut self, _: Option<i64>, _: Option<i64>, _: Option<i64> ); 
+    fn jump_if_true( &mut self, _: Option<i64>, _: Option<i64>, _: Option<i64> ); 
+    fn jump_if_false( &mut self, _: Option<i64>, _: Option<i64>, _: Option<i64> ); 
+    fn less_than( &mut self, _: Option<i64>, _: Option<i64>, _: Option<i64> ); 
+    fn equals( &mut self, _: Option<i64>, _: Option<i64>, _: Option<i64> ); 
+    fn add_relative_offset( &mut self, _: Option<i64>, _: Option<i64>, _: Option<i64> ); 
+    fn execute_instruction( &mut self );
+    fn execute_instructions( &mut self );
+    fn try_execute_instructions( &mut self ) -> Result< (), ExecutionError >;
+    #[allow(dead_code)]
+    fn print_initial_value( &mut self );
+    fn get_output_value( &mut self ) -> Option< i64 >;
+    #[allow(dead_code)]
+    fn set_inputs( &mut self, inputs: Vec<i64> );
+    #[allow(dead_code)]
+    fn set_inputs_consumer( &mut self, _: T );
+    #[allow(dead_code)]
+    fn set_outputs_producer( &mut self, _: U );
+    fn get_inputs_consumer( &mut self ) -> &mut T;
+    fn get_outputs_producer( &mut self ) -> &mut U;
+    fn display_std_out( &mut self, disp_std_out: bool );
+    fn pause_execution_on_output( &mut self, pause_execution: bool );
+    fn has_finished( &self ) -> bool;
+    #[allow(dead_code)]
+    fn has_value( &mut self ) -> bool;
+    fn get_previous_instruction( &self ) -> usize;
+    fn run_until_num_out( &mut self, _: usize );
+    #[allow(dead_code)]
+    fn try_run_until_num_out( &mut self, _: usize ) -> Result< (), ExecutionError >;
+    fn set_instruction_limit( &mut self, limit: Option<u64> );
+    // the clock is only read between instructions, a consumer that blocks waiting for input
+    // (stdin, a blocking channel) holds the machine up past the limit
+    fn set_time_limit( &mut self, limit: Option<Duration> );
+    fn set_checked_arithmetic( &mut self, checked: bool );
+    fn record_coverage( &mut self, record: bool );
+    fn get_coverage( &self ) -> Option< &HashMap< usize, u64 > >;
+    fn track_calls( &mut self, tracker: Option< CallTracker > );
+    fn get_call_tracker( &self ) -> Option< &CallTracker >;
+    fn get_step_count( &self ) -> u64;
+    fn get_error( &self ) -> Option< ExecutionError >;
+    fn get_memory( &self ) -> &HashMap< usize, i64 >;
+    fn read_memory( &self, idx: usize ) -> i64;
+    fn write_memory( &mut self, idx: usize, value: i64 );
+    fn get_instruction_pointer( &self ) -> usize;
+    fn set_instruction_pointer( &mut self, idx: usize );
+    fn get_relative_offset( &self ) -> i64;
+    fn set_relative_offset( &mut self, offset: i64 );
+    #[allow(dead_code)]
+    fn map_device( &mut self, start: usize, end: usize, device: Rc<RefCell<dyn MemoryDevice>> );
+    fn add_observer( &mut self, observer: Rc<RefCell<dyn Observer>> );
+    #[allow(dead_code)]
+    fn clear_observers( &mut self );
+    #[allow(dead_code)]
+    fn record_history( &mut self, record: bool, capacity: Option<usize> );
+    #[allow(dead_code)]
+    fn get_history( &self ) -> Option< &UndoLog >;
+    #[allow(dead_code)]
+    fn step_back( &mut self, steps: usize ) -> usize;
+    #[allow(dead_code)]
+    fn run_back_to( &mut self, breakpoint: usize ) -> bool;
+
+    fn private_read_parameter( &mut self, parameter_mode: Option<i64> ) -> i64;
+    fn private_write_parameter( &mut self, parameter_mode: Option<i64>, output: i64 );
+    fn read_digit( &mut self, digit: i64, place: u32 ) -> Option< i64 >;
+    fn private_check_memory( &mut self, idx: usize ) -> i64;
+    fn private_get_index( &mut self, param_mode: Option<i64> );
+    fn private_load( &mut self, idx: usize ) -> i64;
+    fn private_store( &mut self, idx: usize, value: i64 );
+    fn private_notify_read( &mut self, idx: usize, value: i64 );
+    fn private_check_limits( &mut self ) -> Option< ExecutionError >;
+    fn private_overflow( &mut self );
+    fn private_address( &mut self, value: i64, relative: bool ) -> Option<usize>;
+}
+
+// ready made consumers and producers live in devices.rs
+pub trait Consumer {
+    fn get_value( &mut self ) -> i64;
+}
+
+pub trait Producer {
+    fn load_value( &mut self, _: i64 );
+}
+
+#[derive(Clone)]
+pub struct CPU<T: Consumer, U: Producer> {
+    dispatcher: HashMap<usize, Instruction<T, U>>,
+    memory: HashMap<usize, i64>,
+    idx: usize,
+    out: Option<i64>,
+    inputs: Vec<i64>,
+    disp_std_out: bool,
+    pause_execution: bool,
+    finished: bool,
+    relative_offset: i64,
+    input_consumer: T,
+    output_producer: U,
+    previous_instruction: usize,
+    num_output: usize,
+    devices: Vec<MappedDevice>,
+    steps: u64,
+    instruction_limit: Option<u64>,
+    time_limit: Option<Duration>,
+    start_time: Option<Instant>,
+    error: Option<ExecutionError>,
+    checked_arithmetic: bool,
+    instruction_idx: usize,
+    coverage: Option<HashMap<usize, u64>>,
+    call_tracker: Option<CallTracker>,
+    observers: Vec<Rc<RefCell<dyn Observer>>>,
+    undo_log: Option<UndoLog>,
+    // inputs that were stepped back over, fed again before asking the consumer
+    replay_inputs: Vec<i64>
+}
+
+impl < T: Consumer, U: Producer > Computer<T, U> for CPU< T, U > {
+    fn initialise( memory: HashMap< usize, i64 >, consumer: T, producer: U ) -> CPU<T, U> {
+        let mut dispatcher = HashMap::new();
+        dispatcher.insert( 1, Self::add as Instruction<T, U> );
+        dispatcher.insert( 2, Self::multiply as Instruction<T, U> );
+        dispatcher.insert( 3, Self::read as Instruction<T, U> );
+        dispatcher.insert( 4, Self::write as Instruction<T, U> );
+        dispatcher.insert( 5, Self::jump_if_true as Instruction<T, U> );
+        dispatcher.insert( 6, Self::jump_if_false as Instruction<T, U> );
+        dispatcher.insert( 7, Self::less_than as Instruction<T, U> );
+        dispatcher.insert( 8, Self::equals as Instruction<T, U> );
+        dispatcher.insert( 9, Self::add_relative_offset as Instruction<T, U> );
+        dispatcher.insert( 99, Self::exit as Instruction<T, U> );
+
+        CPU { dispatcher: dispatcher, memory: memory, idx: 0, out: None, inputs: Vec::new(), disp_std_out: true, pause_execution: false, finished: false, relative_offset: 0,
+            input_consumer: consumer,
+            output_producer: producer,
+            previous_instruction: 0,
+            num_output: 0,
+            devices: Vec::new(),
+            steps: 0,
+            instruction_limit: None,
+            time_limit: None,
+            start_time: None,
+            error: None,
+            checked_arithmetic: false,
+            instruction_idx: 0,
+            coverage: None,
+            call_tracker: None,
+            observers: Vec::new(),
+            undo_log: None,
+            replay_inputs: Vec::new()
+         }
+    }
+
+    fn execute_instructions( &mut self ) {
+        while !self.finished && self.error.is_none() && !( self.pause_execution && !self.out.is_none() ) {
+            self.execute_instruction();
+        }
+    }
+
+    fn try_execute_instructions( &mut self ) -> Result< (), ExecutionError > {
+        self.execute_instructions();
+
+        return match self.error.clone() {
+            Some( error ) => Err( error ),
+            None => Ok( () )
+        };
+    }
+
+    fn read_digit( &mut self, digit: i64, place: u32 ) -> Option< i64 > {
+        return Some( digit / ( i64::pow( 10, place - 1 ) ) % 10 );
+    }
+
+    fn execute_instruction( &mut self ) {
+        if self.error.is_some() {
+            return;
+        }
+
+        // an error stops the machine without halting it, run loops check `get_error` as well
+        // as `has_finished` so a runaway program is never mistaken for one that reached 99
+        if let Some( error ) = self.private_check_limits() {
+            self.error = Some( error );
+            return;
+        }
+
+        self.steps += 1;
+
+        if let Some( log ) = self.undo_log.as_mut() {
+            log.begin( UndoEntry::initialise( self.idx, self.relative_offset, self.out, self.num_output, self.finished ) );
+        }
+
+        // checked, running into something that is not an instruction is the program's fault
+        if self.checked_arithmetic {
+            let code = *self.memory.get( &self.idx ).unwrap_or( &0 );
+
+            if code < 0 || !self.dispatcher.contains_key( &( ( code % 100 ) as usize ) ) {
+                self.error = Some( ExecutionError::UnknownOpcode { idx: self.idx, steps: self.steps, opcode: code } );
+                return;
+            }
+        }
+
+        let next_code = self.memory[&self.idx] as usize;
+        let param_mode_one: Option<i64> = self.read_digit( next_code as i64, 3 );
+        let param_mode_two: Option<i64> = self.read_digit( next_code as i64, 4 );
+        let param_mode_three: Option<i64> = self.read_digit( next_code as i64, 5 );
+
+        let instruction = next_code % 100;
+        self.instruction_idx = self.idx;
+        self.idx += 1;
+
+        if let Some( coverage ) = self.coverage.as_mut() {
+            *coverage.entry( self.instruction_idx ).or_insert( 0 ) += 1;
+        }
+
+        if !self.observers.is_empty() {
+            for observer in self.observers.iter() {
+                observer.borrow_mut().instruction_fetched( self.instruction_idx, next_code as i64 );
+            }
+        }
+
+        self.previous_instruction = instruction;
+
+        self.dispatcher[&(instruction)]( self, param_mode_one, param_mode_two, param_mode_three );
+
+        if self.call_tracker.is_some() {
+            let return_slot = *self.memory.get( &( self.relative_offset as usize ) ).unwrap_or( &0 );
+            let ( address, next_idx, relative_offset ) = ( self.instruction_idx, self.idx, self.relative_offset );
+
+            self.call_tracker.as_mut().unwrap().step( address, instruction as i64, next_idx, relative_offset, return_slot );
+        }
+    }
+
+    fn run_until_num_out( &mut self, idx: usize ) {
+        while self.num_output < idx && !self.finished && self.error.is_none() {
+            self.execute_instruction();
+        }
+
+        self.num_output = 0;
+    }
+
+    fn try_run_until_num_out( &mut self, idx: usize ) -> Result< (), ExecutionError > {
+        self.run_until_num_out( idx );
+
+        return match self.error.clone() {
+            Some( error ) => Err( error ),
+            None => Ok( () )
+        };
+    }
+
+    fn set_instruction_limit( &mut self, limit: Option<u64> ) {
+        self.instruction_limit = limit;
+    }
+
+    fn set_time_limit( &mut self, limit: Option<Duration> ) {
+        self.time_limit = limit;
+        self.start_time = None;
+    }
+
+    fn set_checked_arithmetic( &mut self, checked: bool ) {
+        self.checked_arithmetic = checked;
+    }
+
+    fn record_coverage( &mut self, record: bool ) {
+        if !record {
+            self.coverage = None;
+        } else if self.coverage.is_none() {
+            self.coverage = Some( HashMap::new() );
+        }
+    }
+
+    fn get_coverage( &self ) -> Option< &HashMap< usize, u64 > > {
+        return self.coverage.as_ref();
+    }
+
+    fn track_calls( &mut self, tracker: Option< CallTracker > ) {
+        self.call_tracker = tracker;
+    }
+
+    fn get_call_tracker( &self ) -> Option< &CallTracker > {
+        return self.call_tracker.as_ref();
+    }
+
+    fn get_step_count( &self ) -> u64 {
+        return self.steps;
+    }
+
+    fn get_error( &self ) -> Option< ExecutionError > {
+        return self.error.clone();
+    }
+
+    fn get_memory( &self ) -> &HashMap< usize, i64 > {
+        return &self.memory;
+    }
+
+    fn read_memory( &self, idx: usize ) -> i64 {
+        return *self.memory.get( &idx ).unwrap_or( &0 );
+    }
+
+    fn write_memory( &mut self, idx: usize, value: i64 ) {
+        self.memory.insert( idx, value );
+    }
+
+    fn get_instruction_pointer( &self ) -> usize {
+        return self.idx;
+    }
+
+    fn set_instruction_pointer( &mut self, idx: usize ) {
+        self.idx = idx;
+    }
+
+    fn get_relative_offset( &self ) -> i64 {
+        return self.relative_offset;
+    }
+
+    fn set_relative_offset( &mut self, offset: i64 ) {
+        self.relative_offset = offset;
+    }
+
+    fn map_device( &mut self, start: usize, end: usize, device: Rc<RefCell<dyn MemoryDevice>> ) {
+        if self.devices.iter().any( |d| d.overlaps( start, end ) ) {
+            panic!( "device range {}..{} overlaps an existing device", start, end );
+        }
+
+        self.devices.push( MappedDevice::initialise( start, end, device ) );
+    }
+
+    fn add_observer( &mut self, observer: Rc<RefCell<dyn Observer>> ) {
+        self.observers.push( observer );
+    }
+
+    fn clear_observers( &mut self ) {
+        self.observers.clear();
+    }
+
+    fn record_history( &mut self, record: bool, capacity: Option<usize> ) {
+        self.undo_log = if record { Some( UndoLog::initialise( capacity ) ) } else { None };
+    }
+
+    fn get_history( &self ) -> Option< &UndoLog > {
+        return self.undo_log.as_ref();
+    }
+
+    fn step_back( &mut self, steps: usize ) -> usize {
+        let mut undone = 0;
+
+        while undone < steps {
+            let entry = match self.undo_log.as_mut().and_then( |log| log.pop() ) {
+                Some( entry ) => entry,
+                None => break
+            };
+
+            for ( idx, previous ) in entry.writes.iter().rev() {
+                match previous {
+                    Some( value ) => self.memory.insert( *idx, *value ),
+                    None => self.memory.remove( idx )
+                };
+            }
+
+            if let Some( input ) = entry.input {
+                self.replay_inputs.push( input );
+            }
+
+            self.idx = entry.idx;
+            self.relative_offset = entry.relative_offset;
+            self.out = entry.out;
+            self.num_output = entry.num_output;
+            self.finished = entry.finished;
+            self.error = None;
+            self.steps -= 1;
+            undone += 1;
+        }
+
+        return undone;
+    }
+
+    fn run_back_to( &mut self, breakpoint: usize ) -> bool {
+        while self.step_back( 1 ) == 1 {
+            if self.idx == breakpoint {
+                return true;
+            }
+        }
+
+        return false;
+    }
+
+    fn get_previous_instruction( &self ) -> usize {
+        return self.previous_instruction;
+    }
+
+    // TODO: workout how to have this private
+    fn add( &mut self, param_one_mode: Option<i64>, param_two_mode: Option<i64>, param_three_mode: Option<i64> ) {
+        let first_val  : i64 = self.private_read_parameter( param_one_mode );
+        let second_val : i64 = self.private_read_parameter( param_two_mode );
+
+        let output: i64;
+
+        if self.checked_arithmetic {
+            match first_val.checked_add( second_val ) {
+                Some( value ) => output = value,
+                None => {
+                    self.private_overflow();
+                    return;
+                }
+            }
+        } else {
+            output = first_val + second_val;
+        }
+
+        // write into memory
+        self.private_write_parameter( param_three_mode, output );
+    }
+
+    fn multiply( &mut self, param_one_mode: Option<i64>, param_two_mode: Option<i64>, param_three_mode: Option<i64> ) {
+        let first_val  : i64 = self.private_read_parameter( param_one_mode );
+        let second_val : i64 = self.private_read_parameter( param_two_mode );
+
+        let output: i64;
+
+        if self.checked_arithmetic {
+            match first_val.checked_mul( second_val ) {
+                Some( value ) => output = value,
+                None => {
+                    self.private_overflow();
+                    return;
+                }
+            }
+        } else {
+            output = first_val * second_val;
+        }
+
+        // write into memory
+        self.private_write_parameter( param_three_mode, output );
+    }
+
+    fn read( &mut self, param_one_mode: Option<i64>, _: Option<i64>, _: Option<i64> ) {
+        // inputs stepped back over come first, then any from set_inputs (handed out from the
+        // back, as the day cpus do) and only then the consumer
+        let input: i64 = match self.replay_inputs.pop().or_else( || self.inputs.pop() ) {
+            Some( input ) => input,
+            None => Consumer::get_value( &mut self.input_consumer )
+        };
+
+        if let Some( log ) = self.undo_log.as_mut() {
+            log.record_input( input );
+        }
+
+        if !self.observers.is_empty() {
+            for observer in self.observers.iter() {
+                observer.borrow_mut().input_consumed( input );
+            }
+        }
+
+        self.private_write_parameter( param_one_mode, input );
+    }
+
+    fn write( &mut self, param_one_mode: Option<i64>, _: Option<i64>, _: Option<i64> ) {
+        let value = self.private_read_parameter( param_one_mode );
+
+        if self.error.is_some() {
+            return;
+        }
+
+        self.output_producer.load_value( value );
+
+        if !self.observers.is_empty() {
+            for observer in self.observers.iter() {
+                observer.borrow_mut().output_produced( value );
+            }
+        }
+
+        self.out = Some( value );
+        self.num_output += 1;
+    }
+
+    fn jump_if_true( &mut self, param_one_mode: Option<i64>, param_two_mode: Option<i64>, _: Option<i64> ) {
+        let first_val : i64 = self.private_read_parameter( param_one_mode );
+
+        // write to memory
+        if first_val != 0 {
+            self.private_get_index( param_two_mode );
+        } else {
+            self.idx += 1;
+        }
+    }
+
+    fn jump_if_false( &mut self, param_one_mode: Option<i64>, param_two_mode: Option<i64>, _: Option<i64>) {
+        let first_val  : i64 = self.private_read_parameter( param_one_mode );
+
+        // write to memory
+        if first_val == 0 {
+            self.private_get_index( param_two_mode );
+        } else {
+            self.idx += 1;
+        }
+    }
+
+    fn less_than( &mut self, param_one_mode: Option<i64>, param_two_mode: Option<i64>, param_three_mode: Option<i64> ) {
+        let first_val  : i64 = self.private_read_parameter( param_one_mode );
+        let second_val : i64 = self.private_read_parameter( param_two_mode );
+
+        if first_val < second_val {
+            self.private_write_parameter( param_three_mode, 1 );
+        } else {
+            self.private_write_parameter( param_three_mode, 0 );
+        }
+    }
+
+    fn equals( &mut self, param_one_mode: Option<i64>, param_two_mode: Option<i64>, param_three_mode: Option<i64> ) {
+        let first_val  : i64 = self.private_read_parameter( param_one_mode );
+        let second_val : i64 = self.private_read_parameter( param_two_mode );
+
+        if first_val == second_val {
+            self.private_write_parameter( param_three_mode, 1 );
+        } else {
+            self.private_write_parameter( param_three_mode, 0 );
+        }
+    }
+
+    fn add_relative_offset( &mut self, param_one_mode: Option<i64>, _: Option<i64>, _: Option<i64> ) {
+        let offset = self.private_read_parameter( param_one_mode );
+
+        if self.checked_arithmetic {
+            match self.relative_offset.checked_add( offset ) {
+                Some( value ) => self.relative_offset = value,
+                None => self.private_overflow()
+            }
+        } else {
+            self.relative_offset += offset;
+        }
+    }
+
+    fn exit( &mut self, _: Option<i64>, _: Option<i64>, _: Option<i64> ) {
+        self.finished = true;
+
+        if !self.observers.is_empty() {
+            for observer in self.observers.iter() {
+                observer.borrow_mut().halted( self.instruction_idx, self.steps );
+            }
+        }
+    }
+
+    fn print_initial_value( &mut self ) {
+        println!( "{}", self.memory[&0] );
+    }
+
+    fn get_output_value( &mut self ) -> Option< i64 > {
+        let ret = self.out;
+        self.out = None;
+        return ret;
+    }
+
+    fn has_value( &mut self ) -> bool {
+        return self.out.is_some();
+    }
+
+    fn set_inputs( &mut self, inputs: Vec<i64> ) {
+        self.inputs = inputs;
+    }
+
+    fn set_inputs_consumer( &mut self, consumer: T ) {
+        self.input_consumer = consumer;
+    }
+
+    fn set_outputs_producer( &mut self, producer: U ) {
+        self.output_producer = producer;
+    }
+
+    fn get_inputs_consumer( &mut self ) -> &mut T {
+        return &mut self.input_consumer;
+    }
+
+    fn get_outputs_producer( &mut self ) -> &mut U {
+        return &mut self.output_producer;
+    }
+
+    fn display_std_out(&mut self, disp_std_out: bool) {
+        self.disp_std_out = disp_std_out;
+    }
+
+    fn private_read_parameter( &mut self, parameter_mode: Option<i64> ) -> i64 {
+        let idx: usize;
+
+        match parameter_mode {
+            Some( 1 ) => idx = self.idx,
+            Some( 2 ) => {
+                let value = self.private_check_memory( self.idx );
+                idx = self.private_address( value, true ).unwrap_or( 0 );
+            },
+            _ => {
+                let value = self.private_check_memory( self.idx );
+                idx = self.private_address( value, false ).unwrap_or( 0 );
+            }
+        }
+
+        self.idx += 1;
+
+        // a bad address has already stopped the machine, nothing to read
+        if self.error.is_some() {
+            return 0;
+        }
+
+        let value = self.private_load( idx );
+
+        // immediate operands are part of the fetch rather than a data read
+        if parameter_mode != Some( 1 ) {
+            self.private_notify_read( idx, value );
+        }
+
+        return value;
+    }
+
+    fn private_get_index( &mut self, param_mode: Option<i64> ) {
+        let target: i64;
+
+        match param_mode {
+            Some( 1 ) => {
+                target = self.memory[&(self.idx)];
+            },
+            Some( 2 ) => {
+                let value = self.memory[&(self.idx)];
+                let sec_val_pos = match self.private_address( value, true ) {
+                    Some( pos ) => pos,
+                    None => return
+                };
+
+                target = self.private_load( sec_val_pos );
+                self.private_notify_read( sec_val_pos, target );
+            }
+            _ => {
+                let value = self.memory[&(self.idx)];
+                let sec_val_pos = match self.private_address( value, false ) {
+                    Some( pos ) => pos,
+                    None => return
+                };
+
+                target = self.private_load( sec_val_pos );
+                self.private_notify_read( sec_val_pos, target );
+            }
+        }
+
+        if let Some( idx ) = self.private_address( target, false ) {
+            self.idx = idx;
+        }
+    }
+
+    fn private_notify_read( &mut self, idx: usize, value: i64 ) {
+        if !self.observers.is_empty() {
+            for observer in self.observers.iter() {
+                observer.borrow_mut().memory_read( idx, value );
+            }
+        }
+    }
+
+    fn private_check_memory( &mut self, idx: usize ) -> i64 {
+        let ret: i64;
+
+        if !self.memory.contains_key( &idx ) {
+            ret = 0;
+
+            if let Some( log ) = self.undo_log.as_mut() {
+                log.record_write( idx, None );
+            }
+
+            self.memory.insert( idx, 0 );
+        } else {
+            ret = self.memory[&idx];
+        }
+
+        return ret;
+    }
+
+    fn private_write_parameter( &mut self, parameter_mode: Option<i64>, output: i64 ) {
+        let value = self.private_check_memory( self.idx );
+        let output_save = self.private_address( value, parameter_mode == Some( 2 ) );
+
+        // nothing is written once the machine has stopped on an error earlier in the instruction
+        if let ( Some( idx ), None ) = ( output_save, &self.error ) {
+            self.private_store( idx, output );
+        }
+
+        self.idx += 1;
+    }
+
+    fn private_load( &mut self, idx: usize ) -> i64 {
+        // only pay for the device lookup when something has been mapped
+        if !self.devices.is_empty() {
+            if let Some( mapped ) = self.devices.iter().find( |d| d.contains( idx ) ) {
+                return mapped.read( idx );
+            }
+        }
+
+        return self.private_check_memory( idx );
+    }
+
+    fn private_store( &mut self, idx: usize, value: i64 ) {
+        if !self.observers.is_empty() {
+            // for a mapped device this is the plain memory underneath, devices are not read here
+            let old = *self.memory.get( &idx ).unwrap_or( &0 );
+
+            for observer in self.observers.iter() {
+                observer.borrow_mut().memory_written( idx, old, value );
+            }
+        }
+
+        if !self.devices.is_empty() {
+            if let Some( mapped ) = self.devices.iter().find( |d| d.contains( idx ) ) {
+                mapped.write( idx, value );
+                return;
+            }
+        }
+
+        if let Some( log ) = self.undo_log.as_mut() {
+            log.record_write( idx, self.memory.get( &idx ).cloned() );
+        }
+
+        self.memory.insert( idx, value );
+    }
+
+    fn private_check_limits( &mut self ) -> Option< ExecutionError > {
+        if let Some( limit ) = self.instruction_limit {
+            if self.steps >= limit {
+                return Some( ExecutionError::InstructionLimit { idx: self.idx, steps: self.steps } );
+            }
+        }
+
+        if let Some( limit ) = self.time_limit {
+            // clock starts on the first instruction run after the limit was set
+            let start = *self.start_time.get_or_insert_with( Instant::now );
+
+            // reading the clock is slow compared to an instruction, only do it every so often
+            if self.steps % 1024 == 0 && start.elapsed() >= limit {
+                return Some( ExecutionError::TimeLimit { idx: self.idx, steps: self.steps } );
+            }
+        }
+
+        return None;
+    }
+
+    fn private_overflow( &mut self ) {
+        self.error = Some( ExecutionError::Overflow { idx: self.instruction_idx, steps: self.steps } );
+    }
+
+    // turns an operand into a memory address, with checked arithmetic on a negative or
+    // overflowing address stops the machine instead of wrapping round
+    fn private_address( &mut self, value: i64, relative: bool ) -> Option<usize> {
+        if !self.checked_arithmetic {
+            let address = if relative { value + self.relative_offset } else { value };
+            return Some( address as usize );
+        }
+
+        let address = if relative { value.checked_add( self.relative_offset ) } else { Some( value ) };
+
+        match address {
+            Some( address ) if address >= 0 => return Some( address as usize ),
+            Some( address ) => self.error = Some( ExecutionError::BadAddress { idx: self.instruction_idx, steps: self.steps, address: address } ),
+            None => self.error = Some( ExecutionError::Overflow { idx: self.instruction_idx, steps: self.steps } )
+        }
+
+        return None;
+    }
+
+    fn pause_execution_on_output( &mut self, pause_execution: bool ) {
+        self.pause_execution = pause_execution;
+    }
+
+    fn has_finished( &self ) -> bool {
+        return self.finished;
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::devices::{
+        IteratorConsumer,
+        VecProducer
+    };
+    use crate::intcode::process_input;
+
+    fn run_checked( program: &str ) -> ( Result< (), ExecutionError >, Vec<i64> ) {
+        let mut cpu: CPU<IteratorConsumer<std::vec::IntoIter<i64>>, VecProducer> = Computer::initialise(
+            process_input( program ),
+            IteratorConsumer::initialise( vec![ 7 ] ),
+            VecProducer::initialise()
+        );
+
+        cpu.display_std_out( false );
+        cpu.set_checked_arithmetic( true );
+
+        let result = cpu.try_execute_instructions();
+        return ( result, cpu.get_outputs_producer().take() );
+    }
+
+    #[test]
+    fn checked_negative_position_address() {
+        let ( result, _ ) = run_checked( "104,1,1,-1,0,0,99" );
+        assert_eq!( result, Err( ExecutionError::BadAddress { idx: 2, steps: 2, address: -1 } ) );
+    }
+
+    #[test]
+    fn checked_negative_relative_address() {
+        // the write goes to rb - 3 = -1 and must not land anywhere
+        let ( result, outputs ) = run_checked( "109,2,203,-3,204,-3,99" );
+        assert_eq!( result, Err( ExecutionError::BadAddress { idx: 2, steps: 2, address: -1 } ) );
+        assert!( outputs.is_empty() );
+    }
+
+    #[test]
+    fn checked_relative_address_overflow() {
+        let ( result, _ ) = run_checked( "109,9223372036854775807,204,1,99" );
+        assert_eq!( result, Err( ExecutionError::Overflow { idx: 2, steps: 2 } ) );
+    }
+
+    #[test]
+    fn checked_negative_jump_target() {
+        let ( result, _ ) = run_checked( "1105,1,-4,99" );
+        assert_eq!( result, Err( ExecutionError::BadAddress { idx: 0, steps: 1, address: -4 } ) );
+    }
+
+    #[test]
+    fn checked_arithmetic_overflow() {
+        let ( result, _ ) = run_checked( "1101,9223372036854775807,1,0,99" );
+        assert_eq!( result, Err( ExecutionError::Overflow { idx: 0, steps: 1 } ) );
+    }
+
+    #[test]
+    fn checked_valid_program_runs() {
+        let ( result, outputs ) = run_checked( "109,10,203,0,204,0,99" );
+        assert_eq!( result, Ok( () ) );
+        assert_eq!( outputs, vec![ 7 ] );
+    }
+
+    #[test]
+    fn instruction_limit_stops_without_halting() {
+        let mut cpu: CPU<IteratorConsumer<std::vec::IntoIter<i64>>, VecProducer> = Computer::initialise(
+            process_input( "1105,1,0" ),
+            IteratorConsumer::initialise( vec![] ),
+            VecProducer::initialise()
+        );
+
+        cpu.display_std_out( false );
+        cpu.set_instruction_limit( Some( 10 ) );
+
+        assert_eq!( cpu.try_execute_instructions(), Err( ExecutionError::InstructionLimit { idx: 0, steps: 10 } ) );
+        assert!( !cpu.has_finished() );
+
+        // every run loop stops on the error rather than spinning
+        cpu.run_until_num_out( 1 );
+        assert_eq!( cpu.get_step_count(), 10 );
+    }
+
+    #[test]
+    fn checked_error_does_not_halt() {
+        let mut cpu: CPU<IteratorConsumer<std::vec::IntoIter<i64>>, VecProducer> = Computer::initialise(
+            process_input( "1101,9223372036854775807,1,0,99" ),
+            IteratorConsumer::initialise( vec![] ),
+            VecProducer::initialise()
+        );
+
+        cpu.set_checked_arithmetic( true );
+        cpu.execute_instructions();
+
+        assert!( cpu.get_error().is_some() );
+        assert!( !cpu.has_finished() );
+    }
+
+    #[test]
+    fn set_inputs_come_before_the_consumer() {
+        let mut cpu: CPU<IteratorConsumer<std::vec::IntoIter<i64>>, VecProducer> = Computer::initialise(
+            process_input( "3,0,4,0,3,0,4,0,3,0,4,0,99" ),
+            IteratorConsumer::initialise( vec![ 3 ] ),
+            VecProducer::initialise()
+        );
+
+        cpu.display_std_out( false );
+        cpu.set_inputs( vec![ 2, 1 ] );
+        cpu.execute_instructions();
+
+        assert_eq!( cpu.get_outputs_producer().take(), vec![ 1, 2, 3 ] );
+    }
+}
@@ -0,0 +1,378 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::io::{
+    BufReader,
+    BufWriter
+};
+use std::sync::mpsc::{
+    Receiver,
+    Sender
+};
+
+use super::cpu::{
+    Consumer,
+    Producer
+};
+
+// splits a line of text into the integers on it, commas and whitespace both separate values
+#[allow(dead_code)]
+fn parse_values( line: &str ) -> impl Iterator<Item = i64> + '_ {
+    return line.split( |c: char| c == ',' || c.is_whitespace() )
+        .filter( |t| !t.is_empty() )
+        .map( |t| t.parse().expect( "input is not a number" ) );
+}
+
+/// Prompts on stdin for every input, one number per prompt.
+#[allow(dead_code)]
+pub struct StandardConsumer {}
+
+impl Consumer for StandardConsumer {
+    fn get_value( &mut self ) -> i64 {
+        let mut buff = String::new();
+
+        print!( "Enter a value: " );
+        io::stdout().flush().ok().expect( "could not flush" );
+        io::stdin().read_line( &mut buff ).expect( "failed to read line" );
+        return buff.trim().parse().unwrap();
+    }
+}
+
+/// Prints every output on its own line.
+#[allow(dead_code)]
+pub struct StandardProducer {}
+
+impl Producer for StandardProducer {
+    fn load_value( &mut self, value: i64 ) {
+        println!( "{}", value );
+    }
+}
+
+/// Reads a line of text from stdin whenever it runs out and hands it over a character at a
+/// time, newline included, which is what the ASCII programs (day 17, 21, 25) expect.
+pub struct AsciiConsumer {
+    cache: VecDeque<i64>
+}
+
+impl AsciiConsumer {
+    pub fn initialise() -> Self {
+        return AsciiConsumer { cache: VecDeque::new() };
+    }
+}
+
+impl Consumer for AsciiConsumer {
+    fn get_value( &mut self ) -> i64 {
+        if let Some( v ) = self.cache.pop_front() {
+            return v;
+        }
+
+        let mut buff = String::new();
+        io::stdout().flush().ok().expect( "could not flush" );
+        io::stdin().read_line( &mut buff ).expect( "failed to read line" );
+
+        self.cache.extend( buff.chars().map( |c| c as u8 as i64 ) );
+
+        return self.cache.pop_front().expect( "ran out of input" );
+    }
+}
+
+/// Prints every output as a character.
+pub struct AsciiProducer {}
+
+impl Producer for AsciiProducer {
+    fn load_value( &mut self, value: i64 ) {
+        print!( "{}", value as u8 as char );
+    }
+}
+
+/// Inputs taken from any iterator, panics if the program asks for more than it has.
+pub struct IteratorConsumer<I: Iterator<Item = i64>> {
+    values: I
+}
+
+impl<I: Iterator<Item = i64>> IteratorConsumer<I> {
+    pub fn initialise<V: IntoIterator<IntoIter = I, Item = i64>>( values: V ) -> Self {
+        return IteratorConsumer { values: values.into_iter() };
+    }
+}
+
+impl<I: Iterator<Item = i64>> Consumer for IteratorConsumer<I> {
+    fn get_value( &mut self ) -> i64 {
+        return self.values.next().expect( "iterator ran out of input" );
+    }
+}
+
+/// Inputs computed on demand, e.g. from the state of whatever the program is driving.
+pub struct ClosureConsumer<F: FnMut() -> i64> {
+    function: F
+}
+
+impl<F: FnMut() -> i64> ClosureConsumer<F> {
+    pub fn initialise( function: F ) -> Self {
+        return ClosureConsumer { function: function };
+    }
+}
+
+impl<F: FnMut() -> i64> Consumer for ClosureConsumer<F> {
+    fn get_value( &mut self ) -> i64 {
+        return ( self.function )();
+    }
+}
+
+/// Inputs read from a file (or any other reader) of numbers, separated by commas,
+/// whitespace or newlines. The reader is only read from as values are needed.
+#[allow(dead_code)]
+pub struct FileConsumer<R: BufRead> {
+    reader: R,
+    cache: VecDeque<i64>
+}
+
+#[allow(dead_code)]
+impl FileConsumer<BufReader<File>> {
+    pub fn open( path: &str ) -> io::Result<Self> {
+        return Ok( FileConsumer::from_reader( BufReader::new( File::open( path )? ) ) );
+    }
+}
+
+#[allow(dead_code)]
+impl<R: BufRead> FileConsumer<R> {
+    pub fn from_reader( reader: R ) -> Self {
+        return FileConsumer { reader: reader, cache: VecDeque::new() };
+    }
+}
+
+impl<R: BufRead> Consumer for FileConsumer<R> {
+    fn get_value( &mut self ) -> i64 {
+        while self.cache.is_empty() {
+            let mut line = String::new();
+
+            if self.reader.read_line( &mut line ).expect( "failed to read input" ) == 0 {
+                panic!( "input file ran out of values" );
+            }
+
+            self.cache.extend( parse_values( &line ) );
+        }
+
+        return self.cache.pop_front().unwrap();
+    }
+}
+
+/// Inputs received on a channel. Without an idle value it blocks until one arrives, with
+/// one it returns that value straight away when nothing is waiting (day 23 uses -1).
+#[allow(dead_code)]
+pub struct ChannelConsumer {
+    receiver: Receiver<i64>,
+    idle_value: Option<i64>
+}
+
+#[allow(dead_code)]
+impl ChannelConsumer {
+    pub fn initialise( receiver: Receiver<i64>, idle_value: Option<i64> ) -> Self {
+        return ChannelConsumer { receiver: receiver, idle_value: idle_value };
+    }
+}
+
+impl Consumer for ChannelConsumer {
+    fn get_value( &mut self ) -> i64 {
+        return match self.idle_value {
+            Some( idle ) => self.receiver.try_recv().unwrap_or( idle ),
+            None => self.receiver.recv().expect( "input channel closed" )
+        };
+    }
+}
+
+/// Collects every output, read them back through `get_outputs_producer`.
+#[derive(Clone, Default)]
+pub struct VecProducer {
+    values: Vec<i64>
+}
+
+impl VecProducer {
+    pub fn initialise() -> Self {
+        return VecProducer { values: Vec::new() };
+    }
+
+    #[allow(dead_code)]
+    pub fn values( &self ) -> &Vec<i64> {
+        return &self.values;
+    }
+
+    pub fn take( &mut self ) -> Vec<i64> {
+        return std::mem::take( &mut self.values );
+    }
+}
+
+impl Producer for VecProducer {
+    fn load_value( &mut self, value: i64 ) {
+        self.values.push( value );
+    }
+}
+
+/// Hands every output to a closure.
+pub struct ClosureProducer<F: FnMut( i64 )> {
+    function: F
+}
+
+impl<F: FnMut( i64 )> ClosureProducer<F> {
+    pub fn initialise( function: F ) -> Self {
+        return ClosureProducer { function: function };
+    }
+}
+
+impl<F: FnMut( i64 )> Producer for ClosureProducer<F> {
+    fn load_value( &mut self, value: i64 ) {
+        ( self.function )( value );
+    }
+}
+
+/// Writes every output to a file (or any other writer), one number per line.
+pub struct FileProducer<W: Write> {
+    writer: W
+}
+
+impl FileProducer<BufWriter<File>> {
+    pub fn create( path: &str ) -> io::Result<Self> {
+        return Ok( FileProducer::from_writer( BufWriter::new( File::create( path )? ) ) );
+    }
+}
+
+impl<W: Write> FileProducer<W> {
+    pub fn from_writer( writer: W ) -> Self {
+        return FileProducer { writer: writer };
+    }
+
+    pub fn flush( &mut self ) -> io::Result<()> {
+        return self.writer.flush();
+    }
+}
+
+impl<W: Write> Producer for FileProducer<W> {
+    fn load_value( &mut self, value: i64 ) {
+        writeln!( self.writer, "{}", value ).expect( "failed to write output" );
+    }
+}
+
+/// Sends every output down a channel, an output nobody is listening for is dropped.
+#[allow(dead_code)]
+pub struct ChannelProducer {
+    sender: Sender<i64>
+}
+
+#[allow(dead_code)]
+impl ChannelProducer {
+    pub fn initialise( sender: Sender<i64> ) -> Self {
+        return ChannelProducer { sender: sender };
+    }
+}
+
+impl Producer for ChannelProducer {
+    fn load_value( &mut self, value: i64 ) {
+        self.sender.send( value ).ok();
+    }
+}
+
+/// Every output goes to both producers, e.g. to log a run while something else reacts to it.
+#[allow(dead_code)]
+pub struct Tee<A: Producer, B: Producer> {
+    first: A,
+    second: B
+}
+
+#[allow(dead_code)]
+impl<A: Producer, B: Producer> Tee<A, B> {
+    pub fn initialise( first: A, second: B ) -> Self {
+        return Tee { first: first, second: second };
+    }
+
+    pub fn first( &mut self ) -> &mut A {
+        return &mut self.first;
+    }
+
+    pub fn second( &mut self ) -> &mut B {
+        return &mut self.second;
+    }
+}
+
+impl<A: Producer, B: Producer> Producer for Tee<A, B> {
+    fn load_value( &mut self, value: i64 ) {
+        self.first.load_value( value );
+        self.second.load_value( value );
+    }
+}
+
+/// Passes inputs through from a consumer and copies each one to a producer, so the inputs
+/// of a session can be recorded (and later replayed with a `FileConsumer`).
+#[allow(dead_code)]
+pub struct TeeConsumer<C: Consumer, P: Producer> {
+    consumer: C,
+    recorder: P
+}
+
+#[allow(dead_code)]
+impl<C: Consumer, P: Producer> TeeConsumer<C, P> {
+    pub fn initialise( consumer: C, recorder: P ) -> Self {
+        return TeeConsumer { consumer: consumer, recorder: recorder };
+    }
+
+    pub fn recorder( &mut self ) -> &mut P {
+        return &mut self.recorder;
+    }
+}
+
+impl<C: Consumer, P: Producer> Consumer for TeeConsumer<C, P> {
+    fn get_value( &mut self ) -> i64 {
+        let value = self.consumer.get_value();
+        self.recorder.load_value( value );
+        return value;
+    }
+}
+
+/// Groups outputs into packets of a fixed size and sends each packet to one of several
+/// producers. The route closure sees the whole packet and picks the producer by index,
+/// returning None drops the packet. With a packet size of 3 and routing on the first value
+/// this is day 23's network card.
+#[allow(dead_code)]
+pub struct Multiplex<P: Producer, F: FnMut( &[i64] ) -> Option<usize>> {
+    producers: Vec<P>,
+    route: F,
+    packet_size: usize,
+    packet: Vec<i64>
+}
+
+#[allow(dead_code)]
+impl<P: Producer, F: FnMut( &[i64] ) -> Option<usize>> Multiplex<P, F> {
+    pub fn initialise( producers: Vec<P>, packet_size: usize, route: F ) -> Self {
+        return Multiplex {
+            producers: producers,
+            route: route,
+            packet_size: usize::max( packet_size, 1 ),
+            packet: Vec::new()
+        };
+    }
+
+    pub fn producers( &mut self ) -> &mut Vec<P> {
+        return &mut self.producers;
+    }
+}
+
+impl<P: Producer, F: FnMut( &[i64] ) -> Option<usize>> Producer for Multiplex<P, F> {
+    fn load_value( &mut self, value: i64 ) {
+        self.packet.push( value );
+
+        if self.packet.len() < self.packet_size {
+            return;
+        }
+
+        let destination = ( self.route )( &self.packet );
+        let producers = &mut self.producers;
+
+        if let Some( producer ) = destination.and_then( |i| producers.get_mut( i ) ) {
+            for value in self.packet.iter() {
+                producer.load_value( *value );
+            }
+        }
+
+        self.packet.clear();
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::{
+    BTreeSet,
+    HashMap
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Parameter {
+    Position( i64 ),
+    Immediate( i64 ),
+    Relative( i64 )
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedInstruction {
+    pub address: usize,
+    pub opcode: i64,
+    pub parameters: Vec<Parameter>
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Line {
+    Code( DecodedInstruction ),
+    Data( usize, i64 )
+}
+
+// number of parameters for each opcode, None for anything that is not an instruction
+pub fn parameter_count( opcode: i64 ) -> Option<usize> {
+    match opcode {
+        1 | 2 | 7 | 8 => Some( 3 ),
+        5 | 6 => Some( 2 ),
+        3 | 4 | 9 => Some( 1 ),
+        99 => Some( 0 ),
+        _ => None
+    }
+}
+
+pub fn mnemonic( opcode: i64 ) -> &'static str {
+    match opcode {
+        1 => "add",
+        2 => "mul",
+        3 => "in",
+        4 => "out",
+        5 => "jt",
+        6 => "jf",
+        7 => "lt",
+        8 => "eq",
+        9 => "arb",
+        99 => "hlt",
+        _ => "???"
+    }
+}
+
+impl DecodedInstruction {
+    pub fn len( &self ) -> usize {
+        return self.parameters.len() + 1;
+    }
+
+    // parameter index that gets written to, if any
+    pub fn write_parameter( &self ) -> Option<usize> {
+        match self.opcode {
+            1 | 2 | 7 | 8 => Some( 2 ),
+            3 => Some( 0 ),
+            _ => None
+        }
+    }
+
+    pub fn is_jump( &self ) -> bool {
+        return self.opcode == 5 || self.opcode == 6;
+    }
+}
+
+impl Parameter {
+    pub fn raw( &self ) -> i64 {
+        match self {
+            Parameter::Position( v ) | Parameter::Immediate( v ) | Parameter::Relative( v ) => *v
+        }
+    }
+
+    pub fn mode( &self ) -> i64 {
+        match self {
+            Parameter::Position( _ ) => 0,
+            Parameter::Immediate( _ ) => 1,
+            Parameter::Relative( _ ) => 2
+        }
+    }
+}
+
+impl std::fmt::Display for Parameter {
+    fn fmt( &self, f: &mut std::fmt::Formatter ) -> std::fmt::Result {
+        match self {
+            Parameter::Position( v ) => write!( f, "[{}]", v ),
+            Parameter::Immediate( v ) => write!( f, "{}", v ),
+            Parameter::Relative( v ) if *v < 0 => write!( f, "[rb-{}]", -v ),
+            Parameter::Relative( v ) => write!( f, "[rb+{}]", v )
+        }
+    }
+}
+
+impl std::fmt::Display for DecodedInstruction {
+    fn fmt( &self, f: &mut std::fmt::Formatter ) -> std::fmt::Result {
+        let parameters: Vec<String> = self.parameters.iter()
+            .map( |p| p.to_string() )
+            .collect();
+
+        write!( f, "{:<4}{}", mnemonic( self.opcode ), parameters.join( ", " ) )
+    }
+}
+
+pub fn cell( program: &HashMap<usize, i64>, idx: usize ) -> i64 {
+    return *program.get( &idx ).unwrap_or( &0 );
+}
+
+pub fn program_len( program: &HashMap<usize, i64> ) -> usize {
+    return program.keys().max().map( |m| m + 1 ).unwrap_or( 0 );
+}
+
+/// Decode the instruction at the given address, returns None if the cell is not a valid
+/// opcode / parameter mode combination (writes can not be immediate).
+pub fn decode( program: &HashMap<usize, i64>, address: usize ) -> Option<DecodedInstruction> {
+    let code = cell( program, address );
+
+    if code < 0 {
+        return None;
+    }
+
+    let opcode = code % 100;
+    let count = parameter_count( opcode )?;
+
+    if code / ( 100 * i64::pow( 10, count as u32 ) ) != 0 {
+        return None;
+    }
+
+    let mut parameters = Vec::new();
+
+    for i in 0..count {
+        let raw = cell( program, address + i + 1 );
+
+        let parameter = match code / i64::pow( 10, i as u32 + 2 ) % 10 {
+            0 => Parameter::Position( raw ),
+            1 => Parameter::Immediate( raw ),
+            2 => Parameter::Relative( raw ),
+            _ => return None
+        };
+
+        parameters.push( parameter );
+    }
+
+    let instruction = DecodedInstruction { address: address, opcode: opcode, parameters: parameters };
+
+    if let Some( write ) = instruction.write_parameter() {
+        if let Parameter::Immediate( _ ) = instruction.parameters[write] {
+            return None;
+        }
+    }
+
+    return Some( instruction );
+}
+
+/// Split the program into code and data by following the control flow from address 0.
+/// Only immediate jump targets can be followed statically, so extra entry points (for
+/// example addresses seen executing in a coverage run) can be passed in as roots.
+pub fn find_code( program: &HashMap<usize, i64>, roots: &[usize] ) -> BTreeSet<usize> {
+    let mut code: BTreeSet<usize> = BTreeSet::new();
+    let mut stack: Vec<usize> = vec![0];
+    let len = program_len( program );
+
+    stack.extend( roots.iter() );
+
+    while let Some( address ) = stack.pop() {
+        if address >= len || code.contains( &address ) {
+            continue;
+        }
+
+        let instruction = match decode( program, address ) {
+            Some( instruction ) => instruction,
+            None => continue
+        };
+
+        code.insert( address );
+
+        if instruction.opcode == 99 {
+            continue;
+        }
+
+        if instruction.is_jump() {
+            let condition = instruction.parameters[0];
+
+            if let Parameter::Immediate( target ) = instruction.parameters[1] {
+                if target >= 0 {
+                    stack.push( target as usize );
+                }
+            }
+
+            // jt 0 / jf 1 never jump, jt 1 / jf 0 always jump
+            let always_jumps = match condition {
+                Parameter::Immediate( v ) => ( v != 0 ) == ( instruction.opcode == 5 ),
+                _ => false
+            };
+
+            if always_jumps {
+                continue;
+            }
+        }
+
+        stack.push( address + instruction.len() );
+    }
+
+    return code;
+}
+
+pub fn disassemble_with_code( program: &HashMap<usize, i64>, code: &BTreeSet<usize> ) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut address = 0;
+    let len = program_len( program );
+
+    while address < len {
+        let decoded = match code.contains( &address ) {
+            true => decode( program, address ),
+            false => None
+        };
+
+        match decoded {
+            // an instruction that runs into the next known instruction is not real
+            Some( instruction ) if ( address + 1..address + instruction.len() ).all( |a| !code.contains( &a ) ) => {
+                address += instruction.len();
+                lines.push( Line::Code( instruction ) );
+            },
+            _ => {
+                lines.push( Line::Data( address, cell( program, address ) ) );
+                address += 1;
+            }
+        }
+    }
+
+    return lines;
+}
+
+pub fn disassemble( program: &HashMap<usize, i64> ) -> Vec<Line> {
+    return disassemble_with_code( program, &find_code( program, &[] ) );
+}
+
+pub fn format_line( line: &Line ) -> String {
+    match line {
+        Line::Code( instruction ) => format!( "{:>6}: {}", instruction.address, instruction ),
+        Line::Data( address, value ) => format!( "{:>6}: {:<4}{}", address, "dat", value )
+    }
+}
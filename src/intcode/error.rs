@@ -0,0 +1,30 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutionError {
+    InstructionLimit { idx: usize, steps: u64 },
+    TimeLimit { idx: usize, steps: u64 },
+    Overflow { idx: usize, steps: u64 },
+    BadAddress { idx: usize, steps: u64, address: i64 },
+    UnknownOpcode { idx: usize, steps: u64, opcode: i64 },
+    InputExhausted { idx: usize, steps: u64 }
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        match self {
+            ExecutionError::InstructionLimit { idx, steps } =>
+                write!( f, "instruction limit reached at idx {} after {} steps", idx, steps ),
+            ExecutionError::TimeLimit { idx, steps } =>
+                write!( f, "time limit reached at idx {} after {} steps", idx, steps ),
+            ExecutionError::Overflow { idx, steps } =>
+                write!( f, "arithmetic overflow in instruction at idx {} after {} steps", idx, steps ),
+            ExecutionError::BadAddress { idx, steps, address } =>
+                write!( f, "negative address {} in instruction at idx {} after {} steps", address, idx, steps ),
+            ExecutionError::UnknownOpcode { idx, steps, opcode } =>
+                write!( f, "unknown opcode {} at idx {} after {} steps", opcode, idx, steps ),
+            ExecutionError::InputExhausted { idx, steps } =>
+                write!( f, "ran out of input at idx {} after {} steps", idx, steps )
+        }
+    }
+}
@@ -0,0 +1,530 @@
+use std::cell::RefCell;
+use std::collections::{
+    HashMap,
+    VecDeque
+};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::rc::Rc;
+
+use regex::Regex;
+
+use super::cpu::{
+    CPU,
+    Computer,
+    Consumer,
+    Producer
+};
+
+// instructions an expect (or a capture waiting for output to settle) may run before giving up
+const DEFAULT_STEP_BUDGET: u64 = 50_000_000;
+
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    Literal( String ),
+    Regex( Regex )
+}
+
+#[derive(Clone, Debug)]
+pub enum Condition {
+    Equals( String, String ),
+    Contains( String, String ),
+    Matches( String, Regex ),
+    Not( Box<Condition> )
+}
+
+// commands with the script line they came from
+pub type Block = Vec<( usize, Command )>;
+
+#[derive(Clone, Debug)]
+pub enum Command {
+    Expect( Pattern ),
+    ExpectHalt,
+    Send( String ),
+    Capture( String, Regex ),
+    Print( String ),
+    Interact,
+    If( Condition, Block, Block )
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptError {
+    Parse { line: usize, message: String },
+    Halted { line: usize, expected: String },
+    WaitingForInput { line: usize, expected: String },
+    StepBudget { line: usize, expected: String }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        match self {
+            ScriptError::Parse { line, message } =>
+                write!( f, "line {}: {}", line, message ),
+            ScriptError::Halted { line, expected } =>
+                write!( f, "line {}: program halted while expecting {}", line, expected ),
+            ScriptError::WaitingForInput { line, expected } =>
+                write!( f, "line {}: program wants input but never printed {}", line, expected ),
+            ScriptError::StepBudget { line, expected } =>
+                write!( f, "line {}: step budget used up while expecting {}", line, expected )
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word( String ),
+    Text( String ),
+    Pattern( String )
+}
+
+fn tokenise( line: &str, number: usize ) -> Result<Vec<Token>, ScriptError> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some( c ) = chars.next() {
+        match c {
+            '#' => break,
+            c if c.is_whitespace() => continue,
+            '"' | '/' => {
+                let mut value = String::new();
+                let mut closed = false;
+
+                while let Some( n ) = chars.next() {
+                    if n == c {
+                        closed = true;
+                        break;
+                    }
+
+                    if n == '\\' {
+                        match ( c, chars.next() ) {
+                            ( '"', Some( 'n' ) ) => value.push( '\n' ),
+                            ( '"', Some( e ) ) => value.push( e ),
+                            // regex escapes are left for the regex, apart from the delimiter
+                            ( _, Some( '/' ) ) => value.push( '/' ),
+                            ( _, Some( e ) ) => { value.push( '\\' ); value.push( e ); },
+                            ( _, None ) => break
+                        }
+                    } else {
+                        value.push( n );
+                    }
+                }
+
+                if !closed {
+                    return Err( ScriptError::Parse { line: number, message: format!( "unterminated {}", c ) } );
+                }
+
+                tokens.push( if c == '"' { Token::Text( value ) } else { Token::Pattern( value ) } );
+            },
+            _ => {
+                let mut word = c.to_string();
+
+                while let Some( n ) = chars.peek() {
+                    if n.is_whitespace() {
+                        break;
+                    }
+
+                    word.push( chars.next().unwrap() );
+                }
+
+                tokens.push( Token::Word( word ) );
+            }
+        }
+    }
+
+    return Ok( tokens );
+}
+
+fn compile( pattern: &str, number: usize ) -> Result<Regex, ScriptError> {
+    return Regex::new( pattern ).map_err( |e| ScriptError::Parse { line: number, message: e.to_string() } );
+}
+
+fn parse_condition( tokens: &[Token], number: usize ) -> Result<Condition, ScriptError> {
+    let error = |message: &str| ScriptError::Parse { line: number, message: message.to_string() };
+
+    match tokens {
+        [ Token::Word( not ), rest @ .. ] if not == "not" =>
+            Ok( Condition::Not( Box::new( parse_condition( rest, number )? ) ) ),
+        [ Token::Word( name ), Token::Word( op ), Token::Text( value ) ] if op == "==" =>
+            Ok( Condition::Equals( name.clone(), value.clone() ) ),
+        [ Token::Word( name ), Token::Word( op ), Token::Text( value ) ] if op == "!=" =>
+            Ok( Condition::Not( Box::new( Condition::Equals( name.clone(), value.clone() ) ) ) ),
+        [ Token::Word( name ), Token::Word( op ), Token::Text( value ) ] if op == "contains" =>
+            Ok( Condition::Contains( name.clone(), value.clone() ) ),
+        [ Token::Word( name ), Token::Word( op ), Token::Pattern( pattern ) ] if op == "matches" =>
+            Ok( Condition::Matches( name.clone(), compile( pattern, number )? ) ),
+        _ => Err( error( "expected a condition: <name> ==|!=|contains \"text\" or <name> matches /regex/" ) )
+    }
+}
+
+// parse commands up to a closing else / end, returns the commands and the word that closed them
+fn parse_block<'a, I: Iterator<Item = ( usize, &'a str )>>( lines: &mut I, nested: bool ) -> Result<( Block, Option<String> ), ScriptError> {
+    let mut commands = Vec::new();
+
+    while let Some( ( number, line ) ) = lines.next() {
+        let tokens = tokenise( line, number )?;
+        let error = |message: &str| ScriptError::Parse { line: number, message: message.to_string() };
+
+        let keyword = match tokens.first() {
+            Some( Token::Word( keyword ) ) => keyword.as_str(),
+            Some( _ ) => return Err( error( "expected a command" ) ),
+            None => continue
+        };
+
+        let command = match ( keyword, &tokens[1..] ) {
+            ( "expect", [ Token::Text( text ) ] ) => Command::Expect( Pattern::Literal( text.clone() ) ),
+            ( "expect", [ Token::Pattern( pattern ) ] ) => Command::Expect( Pattern::Regex( compile( pattern, number )? ) ),
+            ( "expect", [ Token::Word( halt ) ] ) if halt == "halt" => Command::ExpectHalt,
+            ( "send", [ Token::Text( text ) ] ) => Command::Send( text.clone() ),
+            ( "capture", [ Token::Word( name ), Token::Pattern( pattern ) ] ) => Command::Capture( name.clone(), compile( pattern, number )? ),
+            ( "print", [ Token::Text( text ) ] ) => Command::Print( text.clone() ),
+            ( "interact", [] ) => Command::Interact,
+            ( "if", condition ) => {
+                let condition = parse_condition( condition, number )?;
+                let ( then, closed ) = parse_block( lines, true )?;
+
+                let otherwise = match closed.as_deref() {
+                    Some( "else" ) => {
+                        let ( otherwise, closed ) = parse_block( lines, true )?;
+
+                        if closed.as_deref() != Some( "end" ) {
+                            return Err( error( "if without a matching end" ) );
+                        }
+
+                        otherwise
+                    },
+                    Some( "end" ) => Vec::new(),
+                    _ => return Err( error( "if without a matching end" ) )
+                };
+
+                Command::If( condition, then, otherwise )
+            },
+            ( "else", [] ) | ( "end", [] ) if nested => return Ok( ( commands, Some( keyword.to_string() ) ) ),
+            ( "else", [] ) | ( "end", [] ) => return Err( error( "else / end outside of an if" ) ),
+            _ => return Err( error( &format!( "could not parse '{}'", line.trim() ) ) )
+        };
+
+        commands.push( ( number, command ) );
+    }
+
+    return Ok( ( commands, None ) );
+}
+
+/// Parse a script, one command per line, `#` starts a comment:
+///
+/// - `expect "Command?"` or `expect /regex/`: run until the program prints it
+/// - `expect halt`: run until the program halts
+/// - `send "north"`: type a line (a newline is added), `{name}` is replaced by a capture
+/// - `capture room /== (.*) ==/`: store the first group (or whole match) of the regex,
+///   searched for in everything printed since the last send; empty when there is no match
+/// - `print "text {room}"`: write a line to stdout
+/// - `if room == "Hull Breach"` ... `else` ... `end`, also `!=`, `contains "x"`,
+///   `matches /regex/` and a leading `not`
+/// - `interact`: hand the program over to stdin / stdout until it halts
+pub fn parse_script( script: &str ) -> Result<Block, ScriptError> {
+    let mut lines = script.lines().enumerate().map( |( i, l )| ( i + 1, l ) );
+    let ( commands, _ ) = parse_block( &mut lines, false )?;
+
+    return Ok( commands );
+}
+
+struct ScriptConsumer {
+    inputs: Rc<RefCell<VecDeque<i64>>>
+}
+
+impl Consumer for ScriptConsumer {
+    fn get_value( &mut self ) -> i64 {
+        // the session only steps the CPU onto an input instruction when something is queued
+        return self.inputs.borrow_mut().pop_front().expect( "script ran out of input" );
+    }
+}
+
+struct ScriptProducer {
+    outputs: Rc<RefCell<String>>
+}
+
+impl Producer for ScriptProducer {
+    fn load_value( &mut self, value: i64 ) {
+        let mut outputs = self.outputs.borrow_mut();
+
+        // anything outside ascii (e.g. the hull damage on day 21) is written out as a number
+        if ( 0..128 ).contains( &value ) {
+            outputs.push( value as u8 as char );
+        } else {
+            outputs.push_str( &value.to_string() );
+        }
+    }
+}
+
+enum Progress {
+    Halted,
+    WaitingForInput,
+    OutOfSteps
+}
+
+/// Drives an ASCII intcode program (day 21's springdroid, day 25's adventure...) from a
+/// script. Output is matched as it is produced; the program is only ever stepped onto an
+/// input instruction when there is input queued, so a program asking for input that the
+/// script has not sent yet is reported instead of blocking.
+pub struct ExpectSession {
+    cpu: CPU<ScriptConsumer, ScriptProducer>,
+    inputs: Rc<RefCell<VecDeque<i64>>>,
+    outputs: Rc<RefCell<String>>,
+    // start of the output printed since the last send, and how far expect has matched
+    window_start: usize,
+    cursor: usize,
+    captures: HashMap<String, String>,
+    step_budget: u64,
+    echo: bool
+}
+
+impl ExpectSession {
+    pub fn initialise( program: HashMap<usize, i64> ) -> Self {
+        let inputs = Rc::new( RefCell::new( VecDeque::new() ) );
+        let outputs = Rc::new( RefCell::new( String::new() ) );
+        let consumer = ScriptConsumer { inputs: inputs.clone() };
+        let producer = ScriptProducer { outputs: outputs.clone() };
+        let mut cpu = CPU::initialise( program, consumer, producer );
+
+        cpu.display_std_out( false );
+        cpu.pause_execution_on_output( false );
+
+        return ExpectSession {
+            cpu: cpu,
+            inputs: inputs,
+            outputs: outputs,
+            window_start: 0,
+            cursor: 0,
+            captures: HashMap::new(),
+            step_budget: DEFAULT_STEP_BUDGET,
+            echo: false
+        };
+    }
+
+    #[allow(dead_code)]
+    pub fn set_step_budget( &mut self, budget: u64 ) {
+        self.step_budget = budget;
+    }
+
+    /// Copy everything the program prints, and everything sent to it, to stdout.
+    pub fn echo( &mut self, echo: bool ) {
+        self.echo = echo;
+    }
+
+    #[allow(dead_code)]
+    pub fn transcript( &self ) -> String {
+        return self.outputs.borrow().clone();
+    }
+
+    #[allow(dead_code)]
+    pub fn captures( &self ) -> &HashMap<String, String> {
+        return &self.captures;
+    }
+
+    #[allow(dead_code)]
+    pub fn capture( &self, name: &str ) -> Option<&String> {
+        return self.captures.get( name );
+    }
+
+    fn waiting_for_input( &self ) -> bool {
+        let opcode = self.cpu.read_memory( self.cpu.get_instruction_pointer() ) % 100;
+        return opcode == 3 && self.inputs.borrow().is_empty();
+    }
+
+    // step until the check passes, the program halts or blocks on input, or the budget is gone
+    fn run_until<F: FnMut( &ExpectSession ) -> bool>( &mut self, mut done: F ) -> Result<(), Progress> {
+        let mut steps: u64 = 0;
+        let mut printed = self.outputs.borrow().len();
+
+        loop {
+            if done( self ) {
+                return Ok( () );
+            }
+
+            if self.cpu.has_finished() {
+                return Err( Progress::Halted );
+            }
+
+            if self.waiting_for_input() {
+                return Err( Progress::WaitingForInput );
+            }
+
+            if steps >= self.step_budget {
+                return Err( Progress::OutOfSteps );
+            }
+
+            self.cpu.execute_instruction();
+            steps += 1;
+
+            if self.echo && self.outputs.borrow().len() != printed {
+                let outputs = self.outputs.borrow();
+                print!( "{}", &outputs[printed..] );
+                printed = outputs.len();
+            }
+        }
+    }
+
+    fn find( &self, pattern: &Pattern ) -> Option<usize> {
+        let outputs = self.outputs.borrow();
+        let text = &outputs[self.cursor..];
+
+        let end = match pattern {
+            Pattern::Literal( literal ) => text.find( literal.as_str() ).map( |i| i + literal.len() ),
+            Pattern::Regex( regex ) => regex.find( text ).map( |m| m.end() )
+        };
+
+        return end.map( |e| self.cursor + e );
+    }
+
+    pub fn expect( &mut self, pattern: &Pattern, line: usize ) -> Result<(), ScriptError> {
+        let expected = match pattern {
+            Pattern::Literal( literal ) => format!( "{:?}", literal ),
+            Pattern::Regex( regex ) => format!( "/{}/", regex.as_str() )
+        };
+
+        let mut matched = None;
+        let result = self.run_until( |session| {
+            matched = session.find( pattern );
+            matched.is_some()
+        });
+
+        return match result {
+            Ok( () ) => {
+                self.cursor = matched.unwrap();
+                Ok( () )
+            },
+            Err( Progress::Halted ) => Err( ScriptError::Halted { line: line, expected: expected } ),
+            Err( Progress::WaitingForInput ) => Err( ScriptError::WaitingForInput { line: line, expected: expected } ),
+            Err( Progress::OutOfSteps ) => Err( ScriptError::StepBudget { line: line, expected: expected } )
+        };
+    }
+
+    pub fn send( &mut self, text: &str ) {
+        if self.echo {
+            println!( "{}", text );
+        }
+
+        let mut inputs = self.inputs.borrow_mut();
+        inputs.extend( text.chars().map( |c| c as u8 as i64 ) );
+        inputs.push_back( b'\n' as i64 );
+
+        let len = self.outputs.borrow().len();
+        self.window_start = len;
+        self.cursor = len;
+    }
+
+    /// Let the program print everything it has to say before it next needs input.
+    pub fn settle( &mut self ) {
+        self.run_until( |_| false ).ok();
+    }
+
+    fn substitute( &self, text: &str ) -> String {
+        let mut result = text.to_string();
+
+        for ( name, value ) in self.captures.iter() {
+            result = result.replace( &format!( "{{{}}}", name ), value );
+        }
+
+        return result;
+    }
+
+    fn evaluate( &self, condition: &Condition ) -> bool {
+        let value = |name: &str| self.captures.get( name ).cloned().unwrap_or_default();
+
+        match condition {
+            Condition::Equals( name, text ) => value( name ) == self.substitute( text ),
+            Condition::Contains( name, text ) => value( name ).contains( &self.substitute( text ) ),
+            Condition::Matches( name, regex ) => regex.is_match( &value( name ) ),
+            Condition::Not( condition ) => !self.evaluate( condition )
+        }
+    }
+
+    fn interact( &mut self ) {
+        let echo = self.echo;
+        self.echo = true;
+
+        loop {
+            self.settle();
+
+            if self.cpu.has_finished() {
+                break;
+            }
+
+            let mut buff = String::new();
+            io::stdout().flush().ok();
+
+            if io::stdin().read_line( &mut buff ).unwrap_or( 0 ) == 0 {
+                break;
+            }
+
+            // the line was typed by the user so there is no need to echo it again
+            self.echo = false;
+            self.send( buff.trim_end_matches( &[ '\n', '\r' ][..] ) );
+            self.echo = true;
+        }
+
+        self.echo = echo;
+    }
+
+    pub fn run_commands( &mut self, commands: &[( usize, Command )] ) -> Result<(), ScriptError> {
+        for ( line, command ) in commands.iter() {
+            match command {
+                Command::Expect( pattern ) => self.expect( pattern, *line )?,
+                Command::ExpectHalt => {
+                    let expected = "halt".to_string();
+
+                    match self.run_until( |session| session.cpu.has_finished() ) {
+                        Ok( () ) => {},
+                        Err( Progress::WaitingForInput ) => return Err( ScriptError::WaitingForInput { line: *line, expected: expected } ),
+                        Err( _ ) => return Err( ScriptError::StepBudget { line: *line, expected: expected } )
+                    }
+                },
+                Command::Send( text ) => {
+                    let text = self.substitute( text );
+                    self.send( &text );
+                },
+                Command::Capture( name, regex ) => {
+                    self.settle();
+
+                    let value = {
+                        let outputs = self.outputs.borrow();
+
+                        regex.captures( &outputs[self.window_start..] )
+                            .and_then( |c| c.get( 1 ).or_else( || c.get( 0 ) ) )
+                            .map( |m| m.as_str().to_string() )
+                            .unwrap_or_default()
+                    };
+
+                    self.captures.insert( name.clone(), value );
+                },
+                Command::Print( text ) => println!( "{}", self.substitute( text ) ),
+                Command::Interact => self.interact(),
+                Command::If( condition, then, otherwise ) => {
+                    if self.evaluate( condition ) {
+                        self.run_commands( then )?;
+                    } else {
+                        self.run_commands( otherwise )?;
+                    }
+                }
+            }
+        }
+
+        return Ok( () );
+    }
+
+    pub fn run_script( &mut self, script: &str ) -> Result<(), ScriptError> {
+        let commands = parse_script( script )?;
+        return self.run_commands( &commands );
+    }
+}
+
+/// Run a script file against a program, echoing the session to stdout.
+pub fn run_script_file( program: HashMap<usize, i64>, path: &str ) -> io::Result< Result<ExpectSession, ScriptError> > {
+    let script = fs::read_to_string( path )?;
+    let mut session = ExpectSession::initialise( program );
+
+    session.echo( true );
+
+    return Ok( session.run_script( &script ).map( |_| session ) );
+}
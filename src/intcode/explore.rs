@@ -0,0 +1,494 @@
+use std::cmp::Reverse;
+use std::collections::{
+    BinaryHeap,
+    HashMap,
+    VecDeque
+};
+use std::hash::Hash;
+
+use super::cpu::{
+    CPU,
+    Computer,
+    Consumer
+};
+use super::devices::VecProducer;
+
+// instructions a single move may take before it is treated as a dead end
+const DEFAULT_MOVE_BUDGET: u64 = 1_000_000;
+
+#[derive(Clone, Default)]
+struct MoveConsumer {
+    inputs: VecDeque<i64>
+}
+
+impl Consumer for MoveConsumer {
+    fn get_value( &mut self ) -> i64 {
+        // a move only ever steps onto an input instruction when its inputs are queued
+        return self.inputs.pop_front().expect( "explorer ran out of input" );
+    }
+}
+
+type Machine = CPU<MoveConsumer, VecProducer>;
+pub type Encoder<S, M> = Box<dyn Fn( &S, &M ) -> Vec<i64>>;
+pub type Decoder<S, M> = Box<dyn Fn( &S, &M, &[i64] ) -> Step<S>>;
+
+/// What the decoder makes of the outputs of a move.
+pub enum Step<S> {
+    /// The move leads to a new state that is explored further.
+    Visit( S ),
+    /// The move leads to a state that is recorded but not explored (e.g. a wall).
+    #[allow(dead_code)]
+    Leaf( S ),
+    /// The move is not possible, nothing is recorded.
+    Blocked
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dedup {
+    /// Two nodes are the same when the machines are, memory and registers included.
+    Machine,
+    /// Two nodes are the same when the decoded states are.
+    State,
+    /// Both the machine and the decoded state have to match.
+    #[allow(dead_code)]
+    Both
+}
+
+pub enum Search<S> {
+    BreadthFirst,
+    #[allow(dead_code)]
+    DepthFirst,
+    /// A* towards the goal, with a heuristic that must not overestimate the remaining moves.
+    #[allow(dead_code)]
+    AStar( Box<dyn Fn( &S ) -> u64> )
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ExploreOptions {
+    pub dedup: Dedup,
+    /// Run every move on a fresh copy of the program rather than continuing the machine,
+    /// for programs that answer one query and halt (day 19). Only the decoded state is
+    /// carried from node to node, so this always deduplicates on the state.
+    pub fresh_machine_per_move: bool,
+    pub max_nodes: Option<usize>,
+    pub move_budget: u64
+}
+
+impl Default for ExploreOptions {
+    fn default() -> Self {
+        return ExploreOptions {
+            dedup: Dedup::Machine,
+            fresh_machine_per_move: false,
+            max_nodes: None,
+            move_budget: DEFAULT_MOVE_BUDGET
+        };
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Node<S> {
+    pub state: S,
+    pub depth: u64,
+    // the node this one was first reached from, with the index of the move taken
+    pub parent: Option<( usize, usize )>,
+    pub leaf: bool
+}
+
+/// The explored graph. Node 0 is the start, edges are ( from, move index, to ) and include
+/// moves that led back to a node already seen.
+pub struct Exploration<S, M> {
+    pub nodes: Vec<Node<S>>,
+    pub edges: Vec<( usize, usize, usize )>,
+    #[allow(dead_code)]
+    pub moves: Vec<M>,
+    pub goal: Option<usize>
+}
+
+impl<S, M: Clone> Exploration<S, M> {
+    #[allow(dead_code)]
+    pub fn find<F: Fn( &S ) -> bool>( &self, predicate: F ) -> Option<usize> {
+        return self.nodes.iter().position( |n| predicate( &n.state ) );
+    }
+
+    /// Fewest moves from the start to every node over the explored graph, whatever search
+    /// built it. None for nodes that cannot be reached (only possible when it was cut short).
+    #[allow(dead_code)]
+    pub fn distances( &self ) -> Vec<Option<u64>> {
+        let mut adjacent: Vec<Vec<usize>> = vec![Vec::new(); self.nodes.len()];
+        let mut distances = vec![None; self.nodes.len()];
+        let mut queue = VecDeque::new();
+
+        for ( from, _, to ) in self.edges.iter() {
+            adjacent[*from].push( *to );
+        }
+
+        if !self.nodes.is_empty() {
+            distances[0] = Some( 0 );
+            queue.push_back( 0 );
+        }
+
+        while let Some( node ) = queue.pop_front() {
+            for next in adjacent[node].iter() {
+                if distances[*next].is_none() {
+                    distances[*next] = Some( distances[node].unwrap() + 1 );
+                    queue.push_back( *next );
+                }
+            }
+        }
+
+        return distances;
+    }
+
+    /// The moves along a shortest path from the start to the node.
+    #[allow(dead_code)]
+    pub fn shortest_path( &self, target: usize ) -> Option<Vec<M>> {
+        let mut adjacent: Vec<Vec<( usize, usize )>> = vec![Vec::new(); self.nodes.len()];
+        let mut previous: Vec<Option<( usize, usize )>> = vec![None; self.nodes.len()];
+        let mut seen = vec![false; self.nodes.len()];
+        let mut queue = VecDeque::new();
+
+        for ( from, m, to ) in self.edges.iter() {
+            adjacent[*from].push( ( *m, *to ) );
+        }
+
+        seen[0] = true;
+        queue.push_back( 0 );
+
+        while let Some( node ) = queue.pop_front() {
+            if node == target {
+                break;
+            }
+
+            for ( m, next ) in adjacent[node].iter() {
+                if !seen[*next] {
+                    seen[*next] = true;
+                    previous[*next] = Some( ( node, *m ) );
+                    queue.push_back( *next );
+                }
+            }
+        }
+
+        if !seen.get( target ).cloned().unwrap_or( false ) {
+            return None;
+        }
+
+        let mut path = Vec::new();
+        let mut node = target;
+
+        while let Some( ( parent, m ) ) = previous[node] {
+            path.push( self.moves[m].clone() );
+            node = parent;
+        }
+
+        path.reverse();
+
+        return Some( path );
+    }
+}
+
+/// Explores the states an intcode program can reach. Every node keeps a copy of the
+/// machine, a move feeds the encoded inputs to a clone of it and runs until it wants more
+/// input (or halts), then the decoder turns the outputs into the next state.
+pub struct Explorer<S, M> {
+    program: HashMap<usize, i64>,
+    moves: Vec<M>,
+    encode: Encoder<S, M>,
+    decode: Decoder<S, M>,
+    options: ExploreOptions
+}
+
+// everything that makes two machines behave the same: non zero memory, ip, relative base
+// and whether they have halted
+type MachineKey = ( Vec<( usize, i64 )>, usize, i64, bool );
+
+// the full key is kept rather than a hash of it, so a hash collision can never merge two
+// different nodes
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Key<S> {
+    Machine( MachineKey ),
+    State( S ),
+    Both( MachineKey, S )
+}
+
+fn machine_key( machine: &Machine ) -> MachineKey {
+    let mut memory: Vec<( usize, i64 )> = machine.get_memory().iter()
+        .filter( |( _, v )| **v != 0 )
+        .map( |( a, v )| ( *a, *v ) )
+        .collect();
+
+    memory.sort();
+
+    return ( memory, machine.get_instruction_pointer(), machine.get_relative_offset(), machine.has_finished() );
+}
+
+impl<S: Clone + Eq + Hash, M: Clone> Explorer<S, M> {
+    pub fn initialise<E, D>( program: HashMap<usize, i64>, moves: Vec<M>, encode: E, decode: D ) -> Self
+        where E: Fn( &S, &M ) -> Vec<i64> + 'static,
+              D: Fn( &S, &M, &[i64] ) -> Step<S> + 'static {
+        return Explorer {
+            program: program,
+            moves: moves,
+            encode: Box::new( encode ),
+            decode: Box::new( decode ),
+            options: ExploreOptions::default()
+        };
+    }
+
+    pub fn set_options( &mut self, options: ExploreOptions ) {
+        self.options = options;
+    }
+
+    fn fresh_machine( &self ) -> Machine {
+        let mut machine = CPU::initialise( self.program.clone(), MoveConsumer::default(), VecProducer::initialise() );
+        machine.display_std_out( false );
+        machine.pause_execution_on_output( false );
+        return machine;
+    }
+
+    // None when the move ran out of budget
+    fn run_move( &self, machine: &mut Machine, inputs: Vec<i64> ) -> Option<Vec<i64>> {
+        machine.get_inputs_consumer().inputs.extend( inputs );
+        let mut steps = 0;
+
+        while !machine.has_finished() {
+            let waiting = machine.read_memory( machine.get_instruction_pointer() ) % 100 == 3
+                && machine.get_inputs_consumer().inputs.is_empty();
+
+            if waiting {
+                break;
+            }
+
+            if steps >= self.options.move_budget {
+                return None;
+            }
+
+            machine.execute_instruction();
+            steps += 1;
+        }
+
+        return Some( machine.get_outputs_producer().take() );
+    }
+
+    fn key( &self, machine: &Machine, state: &S ) -> Key<S> {
+        let dedup = if self.options.fresh_machine_per_move { Dedup::State } else { self.options.dedup };
+
+        return match dedup {
+            Dedup::Machine => Key::Machine( machine_key( machine ) ),
+            Dedup::State => Key::State( state.clone() ),
+            Dedup::Both => Key::Both( machine_key( machine ), state.clone() )
+        };
+    }
+
+    /// Explore from the start state, stopping early at the first state the goal accepts.
+    /// Breadth first and A* stop with a shortest path to it; without a goal the whole
+    /// reachable space (up to `max_nodes`) is explored.
+    pub fn explore( &self, start: S, search: Search<S>, goal: Option<&dyn Fn( &S ) -> bool> ) -> Exploration<S, M> {
+        let start_machine = self.fresh_machine();
+
+        let mut exploration = Exploration {
+            nodes: vec![ Node { state: start.clone(), depth: 0, parent: None, leaf: false } ],
+            edges: Vec::new(),
+            moves: self.moves.clone(),
+            goal: None
+        };
+
+        let mut seen: HashMap<Key<S>, usize> = HashMap::new();
+        let mut machines: HashMap<usize, Machine> = HashMap::new();
+        let mut frontier: VecDeque<usize> = VecDeque::new();
+        let mut heap: BinaryHeap<Reverse<( u64, usize )>> = BinaryHeap::new();
+
+        seen.insert( self.key( &start_machine, &start ), 0 );
+        machines.insert( 0, start_machine );
+        frontier.push_back( 0 );
+        heap.push( Reverse( ( 0, 0 ) ) );
+
+        if goal.map( |g| g( &start ) ).unwrap_or( false ) {
+            exploration.goal = Some( 0 );
+            return exploration;
+        }
+
+        loop {
+            let node = match &search {
+                Search::BreadthFirst => frontier.pop_front(),
+                Search::DepthFirst => frontier.pop_back(),
+                Search::AStar( _ ) => heap.pop().map( |Reverse( ( _, n ) )| n )
+            };
+
+            let node = match node {
+                Some( node ) => node,
+                None => break
+            };
+
+            // a* finds the goal when it is taken off the heap, not when it is first seen
+            if let ( Search::AStar( _ ), Some( g ) ) = ( &search, goal ) {
+                if g( &exploration.nodes[node].state ) {
+                    exploration.goal = Some( node );
+                    break;
+                }
+            }
+
+            let machine = match machines.remove( &node ) {
+                Some( machine ) => machine,
+                None => continue
+            };
+
+            let state = exploration.nodes[node].state.clone();
+            let depth = exploration.nodes[node].depth;
+
+            for ( m, movement ) in self.moves.iter().enumerate() {
+                let mut next_machine = if self.options.fresh_machine_per_move { self.fresh_machine() } else { machine.clone() };
+
+                let outputs = match self.run_move( &mut next_machine, ( self.encode )( &state, movement ) ) {
+                    Some( outputs ) => outputs,
+                    None => continue
+                };
+
+                let ( next_state, leaf ) = match ( self.decode )( &state, movement, &outputs ) {
+                    Step::Visit( s ) => ( s, next_machine.has_finished() && !self.options.fresh_machine_per_move ),
+                    Step::Leaf( s ) => ( s, true ),
+                    Step::Blocked => continue
+                };
+
+                let key = self.key( &next_machine, &next_state );
+
+                if let Some( existing ) = seen.get( &key ).cloned() {
+                    exploration.edges.push( ( node, m, existing ) );
+
+                    // a* can reach a node by a shorter route after first seeing it, reopen it
+                    if let Search::AStar( heuristic ) = &search {
+                        if exploration.nodes[existing].depth > depth + 1 {
+                            exploration.nodes[existing].depth = depth + 1;
+                            exploration.nodes[existing].parent = Some( ( node, m ) );
+                            heap.push( Reverse( ( depth + 1 + heuristic( &next_state ), existing ) ) );
+
+                            if !exploration.nodes[existing].leaf {
+                                machines.insert( existing, next_machine );
+                            }
+                        }
+                    }
+
+                    continue;
+                }
+
+                if self.options.max_nodes.map( |max| exploration.nodes.len() >= max ).unwrap_or( false ) {
+                    continue;
+                }
+
+                let next = exploration.nodes.len();
+                let reached_goal = goal.map( |g| g( &next_state ) ).unwrap_or( false );
+
+                if let Search::AStar( heuristic ) = &search {
+                    heap.push( Reverse( ( depth + 1 + heuristic( &next_state ), next ) ) );
+                }
+
+                seen.insert( key, next );
+                exploration.edges.push( ( node, m, next ) );
+                exploration.nodes.push( Node { state: next_state, depth: depth + 1, parent: Some( ( node, m ) ), leaf: leaf } );
+
+                if !leaf {
+                    machines.insert( next, next_machine );
+                    frontier.push_back( next );
+                }
+
+                if reached_goal && !matches!( search, Search::AStar( _ ) ) {
+                    exploration.goal = Some( next );
+                    return exploration;
+                }
+            }
+        }
+
+        return exploration;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::process_input;
+
+    // states are numbers up to 10, a move adds 1 or 2 and a fresh program doubles the sum
+    fn doubling( options: ExploreOptions ) -> Explorer<i64, i64> {
+        // reads x and halts after writing 2x
+        let program = process_input( "3,9,102,2,9,10,4,10,99,0,0" );
+        let mut explorer = Explorer::initialise( program, vec![ 1, 2 ],
+            |state: &i64, m: &i64| vec![ state + m ],
+            |_: &i64, _: &i64, outputs: &[i64]| if outputs[0] > 10 { Step::Blocked } else { Step::Visit( outputs[0] ) } );
+
+        explorer.set_options( ExploreOptions { fresh_machine_per_move: true, ..options } );
+
+        return explorer;
+    }
+
+    #[test]
+    fn fresh_machine_per_move_finds_shortest_path() {
+        let goal = |state: &i64| *state == 10;
+        let exploration = doubling( ExploreOptions::default() ).explore( 0, Search::BreadthFirst, Some( &goal ) );
+
+        assert_eq!( exploration.shortest_path( exploration.goal.unwrap() ), Some( vec![ 2, 1 ] ) );
+        // 2, 4, 6 and 8 are all seen before 10
+        assert_eq!( exploration.nodes.len(), 6 );
+    }
+
+    #[test]
+    fn depth_first_follows_the_last_move() {
+        let goal = |state: &i64| *state == 10;
+        let exploration = doubling( ExploreOptions::default() ).explore( 0, Search::DepthFirst, Some( &goal ) );
+
+        // 4 is expanded before 2, and reaches 10 straight away
+        let states: Vec<i64> = exploration.nodes.iter().map( |n| n.state ).collect();
+        assert_eq!( states, vec![ 0, 2, 4, 10 ] );
+        assert_eq!( exploration.shortest_path( exploration.goal.unwrap() ), Some( vec![ 2, 1 ] ) );
+    }
+
+    #[test]
+    fn max_nodes_cuts_the_search_short() {
+        let options = ExploreOptions { max_nodes: Some( 3 ), ..ExploreOptions::default() };
+        let exploration = doubling( options ).explore( 0, Search::BreadthFirst, None );
+
+        assert_eq!( exploration.nodes.len(), 3 );
+        assert_eq!( exploration.goal, None );
+        assert_eq!( exploration.distances(), vec![ Some( 0 ), Some( 1 ), Some( 1 ) ] );
+    }
+
+    #[test]
+    fn a_star_reopens_a_node_on_a_shorter_route() {
+        // S=0 -> A=1 -> D=3 -> C=4 -> G=5 and S -> B=2 -> C, the program only echoes the move
+        // and the decoder walks this table. A looks closer than B, so C is first reached the
+        // long way round and has to be reopened once B is expanded.
+        let edges: HashMap<i64, Vec<i64>> = [ ( 0, vec![ 1, 2 ] ), ( 1, vec![ 3 ] ), ( 2, vec![ 4 ] ), ( 3, vec![ 4 ] ), ( 4, vec![ 5 ] ) ]
+            .iter().cloned().collect();
+        let mut explorer = Explorer::initialise( process_input( "3,5,4,5,99,0" ), vec![ 0, 1 ],
+            |_: &i64, m: &i64| vec![ *m ],
+            move |state: &i64, _: &i64, outputs: &[i64]| match edges.get( state ).and_then( |e| e.get( outputs[0] as usize ) ) {
+                Some( next ) => Step::Visit( *next ),
+                None => Step::Blocked
+            } );
+
+        explorer.set_options( ExploreOptions { fresh_machine_per_move: true, ..ExploreOptions::default() } );
+
+        let heuristic = |state: &i64| if *state == 2 { 2 } else { 0 };
+        let goal = |state: &i64| *state == 5;
+        let exploration = explorer.explore( 0, Search::AStar( Box::new( heuristic ) ), Some( &goal ) );
+        let goal = exploration.goal.unwrap();
+
+        assert_eq!( exploration.nodes[goal].depth, 3 );
+        assert_eq!( exploration.find( |state| *state == 4 ).map( |c| exploration.nodes[c].parent ), Some( Some( ( 2, 0 ) ) ) );
+        assert_eq!( exploration.shortest_path( goal ), Some( vec![ 1, 0, 0 ] ) );
+    }
+
+    #[test]
+    fn machine_dedup_keeps_distinct_machines() {
+        // adds every input to a running total and writes it out, forever
+        let program = process_input( "3,100,1,100,101,101,4,101,1105,1,0" );
+        let explorer = Explorer::initialise( program, vec![ 1, 2 ],
+            |_: &i64, m: &i64| vec![ *m ],
+            |_: &i64, _: &i64, outputs: &[i64]| if outputs[0] > 5 { Step::Blocked } else { Step::Visit( outputs[0] ) } );
+
+        let goal = |state: &i64| *state == 5;
+        let exploration = explorer.explore( 0, Search::BreadthFirst, Some( &goal ) );
+        let path = exploration.shortest_path( exploration.goal.unwrap() ).unwrap();
+
+        assert_eq!( path.len(), 3 );
+        assert_eq!( path.iter().sum::<i64>(), 5 );
+    }
+}
@@ -0,0 +1,403 @@
+use std::collections::{
+    BTreeSet,
+    HashMap,
+    HashSet,
+    VecDeque
+};
+use std::fs;
+use std::io;
+use std::panic;
+use std::path::Path;
+
+use super::cpu::{
+    CPU,
+    Computer,
+    Consumer,
+    Producer
+};
+use super::error::ExecutionError;
+use super::disasm::{
+    Parameter,
+    decode,
+    program_len
+};
+
+// small deterministic generator so a seed always replays the same session
+struct XorShift {
+    state: u64
+}
+
+impl XorShift {
+    fn initialise( seed: u64 ) -> Self {
+        return XorShift { state: if seed == 0 { 0x2545_F491_4F6C_DD1D } else { seed } };
+    }
+
+    fn next( &mut self ) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        return self.state;
+    }
+
+    fn below( &mut self, n: usize ) -> usize {
+        return ( self.next() % n as u64 ) as usize;
+    }
+}
+
+// hands out the test case, then zeros once it runs dry so the program never blocks
+struct FuzzConsumer {
+    inputs: VecDeque<i64>
+}
+
+impl Consumer for FuzzConsumer {
+    fn get_value( &mut self ) -> i64 {
+        return self.inputs.pop_front().unwrap_or( 0 );
+    }
+}
+
+struct FuzzProducer {}
+
+impl Producer for FuzzProducer {
+    fn load_value( &mut self, _: i64 ) {
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FuzzConfig {
+    pub seed: u64,
+    pub max_inputs: usize,
+    pub instruction_limit: u64
+}
+
+#[derive(Clone, Debug)]
+pub struct Execution {
+    pub coverage: HashSet<usize>,
+    pub outputs: Vec<i64>,
+    // address of the instruction that overflowed, used a negative address or made the CPU panic
+    pub crash_address: Option<usize>
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FuzzStats {
+    pub executions: usize,
+    pub corpus_size: usize,
+    pub addresses_covered: usize,
+    pub distinct_outputs: usize,
+    pub crashes: usize
+}
+
+/// Coverage guided fuzzer for the values a program reads on opcode 3. A test case is kept
+/// in the corpus when it executes an instruction address, or produces an output value,
+/// that no earlier test case did. Programs run with checked arithmetic, test cases that
+/// overflow, use a negative address, run into an unknown opcode or make the CPU panic are kept apart as crashes, one per
+/// faulting instruction. Everything is driven from the seed, so a session can be replayed
+/// exactly, whatever the build profile.
+pub struct Fuzzer {
+    program: HashMap<usize, i64>,
+    config: FuzzConfig,
+    rng: XorShift,
+    dictionary: Vec<i64>,
+    corpus: Vec<Vec<i64>>,
+    crashes: Vec<Vec<i64>>,
+    crash_addresses: HashSet<usize>,
+    covered: HashSet<usize>,
+    outputs: HashSet<i64>,
+    executions: usize
+}
+
+pub fn execute( program: &HashMap<usize, i64>, inputs: &[i64], instruction_limit: u64 ) -> Execution {
+    let consumer = FuzzConsumer { inputs: inputs.iter().cloned().collect() };
+    let mut cpu = CPU::initialise( program.clone(), consumer, FuzzProducer {} );
+    let mut outputs: Vec<i64> = Vec::new();
+
+    let mut address = 0;
+
+    cpu.display_std_out( false );
+    cpu.set_instruction_limit( Some( instruction_limit ) );
+    cpu.set_checked_arithmetic( true );
+    cpu.record_coverage( true );
+
+    // the program's own faults come back as errors, a panic is left for bugs in the CPU itself
+    let result = panic::catch_unwind( panic::AssertUnwindSafe( || {
+        while !cpu.has_finished() && cpu.get_error().is_none() {
+            address = cpu.get_instruction_pointer();
+            cpu.execute_instruction();
+
+            if let Some( value ) = cpu.get_output_value() {
+                outputs.push( value );
+            }
+        }
+    }));
+
+    let crash_address = match ( result, cpu.get_error() ) {
+        ( Err( _ ), _ ) => Some( address ),
+        ( Ok( _ ), Some( ExecutionError::Overflow { idx, .. } ) ) => Some( idx ),
+        ( Ok( _ ), Some( ExecutionError::BadAddress { idx, .. } ) ) => Some( idx ),
+        ( Ok( _ ), Some( ExecutionError::UnknownOpcode { idx, .. } ) ) => Some( idx ),
+        // running out of budget is not a crash
+        ( Ok( _ ), _ ) => None
+    };
+
+    return Execution {
+        coverage: cpu.get_coverage().unwrap().keys().cloned().collect(),
+        outputs: outputs,
+        crash_address: crash_address
+    };
+}
+
+impl Fuzzer {
+    pub fn initialise( program: HashMap<usize, i64>, config: FuzzConfig ) -> Self {
+        // immediate operands are the constants the program compares against, good values to try
+        let mut dictionary: BTreeSet<i64> = [ 0, 1, -1, 2, i64::MAX, i64::MIN ].iter().cloned().collect();
+
+        for address in 0..program_len( &program ) {
+            if let Some( instruction ) = decode( &program, address ) {
+                for parameter in instruction.parameters.iter() {
+                    if let Parameter::Immediate( v ) = parameter {
+                        dictionary.insert( *v );
+                    }
+                }
+            }
+        }
+
+        return Fuzzer {
+            program: program,
+            rng: XorShift::initialise( config.seed ),
+            config: config,
+            dictionary: dictionary.into_iter().collect(),
+            corpus: Vec::new(),
+            crashes: Vec::new(),
+            crash_addresses: HashSet::new(),
+            covered: HashSet::new(),
+            outputs: HashSet::new(),
+            executions: 0
+        };
+    }
+
+    fn random_value( &mut self ) -> i64 {
+        match self.rng.below( 3 ) {
+            0 => self.dictionary[self.rng.below( self.dictionary.len() )],
+            1 => self.rng.below( 21 ) as i64 - 10,
+            _ => self.rng.next() as i64
+        }
+    }
+
+    fn mutate( &mut self, case: &[i64] ) -> Vec<i64> {
+        let mut mutated = case.to_vec();
+        let rounds = 1 + self.rng.below( 3 );
+
+        for _ in 0..rounds {
+            let choice = if mutated.is_empty() { 0 } else { self.rng.below( 6 ) };
+
+            match choice {
+                0 => {
+                    let idx = self.rng.below( mutated.len() + 1 );
+                    let value = self.random_value();
+                    mutated.insert( idx, value );
+                },
+                1 => {
+                    let idx = self.rng.below( mutated.len() );
+                    mutated[idx] = self.random_value();
+                },
+                2 => {
+                    let idx = self.rng.below( mutated.len() );
+                    mutated[idx] = mutated[idx].wrapping_add( if self.rng.below( 2 ) == 0 { 1 } else { -1 } );
+                },
+                3 => {
+                    let idx = self.rng.below( mutated.len() );
+                    mutated.remove( idx );
+                },
+                4 => {
+                    let idx = self.rng.below( mutated.len() );
+                    mutated.insert( idx, mutated[idx] );
+                },
+                _ => {
+                    // splice in the tail of another corpus entry
+                    if !self.corpus.is_empty() {
+                        let other = self.corpus[self.rng.below( self.corpus.len() )].clone();
+                        let cut = self.rng.below( mutated.len() + 1 );
+                        let from = self.rng.below( other.len() + 1 );
+
+                        mutated.truncate( cut );
+                        mutated.extend( other[from..].iter() );
+                    }
+                }
+            }
+        }
+
+        mutated.truncate( self.config.max_inputs );
+
+        return mutated;
+    }
+
+    /// Run one test case and keep it if it found something new, returns whether it was kept.
+    pub fn evaluate( &mut self, case: Vec<i64> ) -> bool {
+        let execution = execute( &self.program, &case, self.config.instruction_limit );
+        self.executions += 1;
+
+        if let Some( address ) = execution.crash_address {
+            if self.crash_addresses.insert( address ) {
+                self.crashes.push( case );
+            }
+
+            return false;
+        }
+
+        let mut interesting = false;
+
+        for address in execution.coverage.iter() {
+            interesting |= self.covered.insert( *address );
+        }
+
+        for value in execution.outputs.iter() {
+            interesting |= self.outputs.insert( *value );
+        }
+
+        if interesting {
+            self.corpus.push( case );
+        }
+
+        return interesting;
+    }
+
+    pub fn run( &mut self, iterations: usize ) -> FuzzStats {
+        if self.corpus.is_empty() {
+            self.evaluate( Vec::new() );
+
+            for value in self.dictionary.clone() {
+                self.evaluate( vec![value] );
+            }
+        }
+
+        for _ in 0..iterations {
+            let parent = if self.corpus.is_empty() {
+                Vec::new()
+            } else {
+                self.corpus[self.rng.below( self.corpus.len() )].clone()
+            };
+
+            let child = self.mutate( &parent );
+            self.evaluate( child );
+        }
+
+        return self.stats();
+    }
+
+    pub fn stats( &self ) -> FuzzStats {
+        return FuzzStats {
+            executions: self.executions,
+            corpus_size: self.corpus.len(),
+            addresses_covered: self.covered.len(),
+            distinct_outputs: self.outputs.len(),
+            crashes: self.crashes.len()
+        };
+    }
+
+    #[allow(dead_code)]
+    pub fn corpus( &self ) -> &Vec<Vec<i64>> {
+        return &self.corpus;
+    }
+
+    #[allow(dead_code)]
+    pub fn crashes( &self ) -> &Vec<Vec<i64>> {
+        return &self.crashes;
+    }
+
+    #[allow(dead_code)]
+    pub fn covered( &self ) -> Vec<usize> {
+        let mut covered: Vec<usize> = self.covered.iter().cloned().collect();
+        covered.sort();
+        return covered;
+    }
+
+    #[allow(dead_code)]
+    pub fn add_seed( &mut self, case: Vec<i64> ) {
+        self.evaluate( case );
+    }
+
+    /// One file per test case, comma separated, crashes get their own prefix.
+    pub fn save_corpus( &self, directory: &str ) -> io::Result<()> {
+        fs::create_dir_all( directory )?;
+
+        let cases = self.corpus.iter().map( |c| ( "case", c ) )
+            .chain( self.crashes.iter().map( |c| ( "crash", c ) ) );
+
+        for ( i, ( prefix, case ) ) in cases.enumerate() {
+            let values: Vec<String> = case.iter().map( |v| v.to_string() ).collect();
+            let path = Path::new( directory ).join( format!( "{}_{:06}.txt", prefix, i ) );
+
+            fs::write( path, values.join( "," ) )?;
+        }
+
+        return Ok( () );
+    }
+
+    #[allow(dead_code)]
+    pub fn load_corpus( &mut self, directory: &str ) -> io::Result<()> {
+        let mut paths: Vec<_> = fs::read_dir( directory )?
+            .filter_map( |e| e.ok().map( |e| e.path() ) )
+            .filter( |p| p.file_name().map( |n| n.to_string_lossy().starts_with( "case_" ) ).unwrap_or( false ) )
+            .collect();
+
+        paths.sort();
+
+        for path in paths {
+            let content = fs::read_to_string( path )?;
+            let case: Vec<i64> = content.trim().split( ',' )
+                .filter( |v| !v.is_empty() )
+                .map( |v| v.trim().parse().unwrap() )
+                .collect();
+
+            self.evaluate( case );
+        }
+
+        return Ok( () );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::process_input;
+
+    // reads x and writes x * 2^62, which overflows for most inputs
+    const PROGRAM: &str = "3,20,1002,20,4611686018427387904,21,4,21,99";
+
+    #[test]
+    fn overflow_is_a_crash_at_the_instruction() {
+        let program = process_input( PROGRAM );
+
+        assert_eq!( execute( &program, &[ 1 ], 1_000 ).crash_address, None );
+        assert_eq!( execute( &program, &[ 2 ], 1_000 ).crash_address, Some( 2 ) );
+        assert_eq!( execute( &program, &[ i64::MIN ], 1_000 ).crash_address, Some( 2 ) );
+    }
+
+    #[test]
+    fn unknown_opcode_is_a_crash() {
+        // jumps onto the 42 unless the input is 0
+        let program = process_input( "3,8,1005,8,9,99,99,99,0,42" );
+
+        assert_eq!( execute( &program, &[ 0 ], 1_000 ).crash_address, None );
+        assert_eq!( execute( &program, &[ 1 ], 1_000 ).crash_address, Some( 9 ) );
+    }
+
+    #[test]
+    fn instruction_limit_is_not_a_crash() {
+        let execution = execute( &process_input( "1105,1,0" ), &[], 100 );
+        assert_eq!( execution.crash_address, None );
+    }
+
+    #[test]
+    fn same_seed_same_session() {
+        let config = FuzzConfig { seed: 7, max_inputs: 4, instruction_limit: 1_000 };
+        let mut first = Fuzzer::initialise( process_input( PROGRAM ), config.clone() );
+        let mut second = Fuzzer::initialise( process_input( PROGRAM ), config );
+
+        first.run( 200 );
+        second.run( 200 );
+
+        assert_eq!( first.corpus(), second.corpus() );
+        assert_eq!( first.crashes(), second.crashes() );
+        assert_eq!( first.stats().crashes, 1 );
+    }
+}
@@ -0,0 +1,516 @@
+use std::collections::BTreeSet;
+use std::io;
+use std::io::prelude::*;
+use std::net::{
+    TcpListener,
+    TcpStream
+};
+
+use super::cpu::{
+    CPU,
+    Computer,
+    Consumer,
+    Producer
+};
+use super::disasm::parameter_count;
+
+// every cell is shown to the debugger as 8 little endian bytes, so cell n lives at byte 8n
+const CELL_SIZE: usize = 8;
+// how many instructions to run between checks for a ctrl-c from the debugger
+const INTERRUPT_POLL: u64 = 4096;
+// largest packet we accept or send, as advertised in qSupported
+const PACKET_SIZE: usize = 0x4000;
+
+const TARGET_XML: &str = "<?xml version=\"1.0\"?>\
+<!DOCTYPE target SYSTEM \"gdb-target.dtd\">\
+<target version=\"1.0\">\
+<feature name=\"org.aoc.intcode\">\
+<reg name=\"ip\" bitsize=\"64\" type=\"code_ptr\" regnum=\"0\"/>\
+<reg name=\"rb\" bitsize=\"64\" type=\"data_ptr\" regnum=\"1\"/>\
+</feature>\
+</target>";
+
+pub enum Reply {
+    Packet( String ),
+    Continue,
+    Close
+}
+
+/// Enough of the GDB remote serial protocol to debug a CPU from gdb (or anything else that
+/// speaks the protocol): memory reads / writes, the ip and rb registers, software
+/// breakpoints, single step and continue. Memory is byte addressed with 8 bytes per cell
+/// and both registers are reported as byte addresses, so `x/gx $rb` does what you expect.
+pub struct GdbStub<T: Consumer, U: Producer> {
+    cpu: CPU<T, U>,
+    breakpoints: BTreeSet<usize>
+}
+
+pub fn checksum( data: &str ) -> String {
+    let sum = data.bytes().fold( 0u8, |acc, b| acc.wrapping_add( b ) );
+    return format!( "{:02x}", sum );
+}
+
+pub fn frame_packet( data: &str ) -> String {
+    return format!( "${}#{}", data, checksum( data ) );
+}
+
+fn hex_u64( value: u64 ) -> String {
+    return value.to_le_bytes().iter()
+        .map( |b| format!( "{:02x}", b ) )
+        .collect();
+}
+
+fn parse_hex_le( data: &str ) -> Option<u64> {
+    let mut bytes = [0u8; 8];
+
+    for ( i, chunk ) in data.as_bytes().chunks( 2 ).take( 8 ).enumerate() {
+        bytes[i] = u8::from_str_radix( std::str::from_utf8( chunk ).ok()?, 16 ).ok()?;
+    }
+
+    return Some( u64::from_le_bytes( bytes ) );
+}
+
+fn parse_address_length( data: &str ) -> Option<( usize, usize )> {
+    let mut parts = data.split( ',' );
+    let address = usize::from_str_radix( parts.next()?, 16 ).ok()?;
+    let length = usize::from_str_radix( parts.next()?, 16 ).ok()?;
+
+    return Some( ( address, length ) );
+}
+
+impl<T: Consumer, U: Producer> GdbStub<T, U> {
+    pub fn initialise( cpu: CPU<T, U> ) -> Self {
+        return GdbStub { cpu: cpu, breakpoints: BTreeSet::new() };
+    }
+
+    #[allow(dead_code)]
+    pub fn get_cpu( &mut self ) -> &mut CPU<T, U> {
+        return &mut self.cpu;
+    }
+
+    fn read_byte( &self, address: usize ) -> u8 {
+        let value = self.cpu.read_memory( address / CELL_SIZE );
+        return value.to_le_bytes()[address % CELL_SIZE];
+    }
+
+    fn write_byte( &mut self, address: usize, byte: u8 ) {
+        let mut bytes = self.cpu.read_memory( address / CELL_SIZE ).to_le_bytes();
+        bytes[address % CELL_SIZE] = byte;
+        self.cpu.write_memory( address / CELL_SIZE, i64::from_le_bytes( bytes ) );
+    }
+
+    fn read_register( &self, register: usize ) -> Option<u64> {
+        match register {
+            0 => Some( ( self.cpu.get_instruction_pointer() * CELL_SIZE ) as u64 ),
+            1 => Some( ( self.cpu.get_relative_offset() * CELL_SIZE as i64 ) as u64 ),
+            _ => None
+        }
+    }
+
+    fn write_register( &mut self, register: usize, value: u64 ) -> bool {
+        match register {
+            0 => self.cpu.set_instruction_pointer( value as usize / CELL_SIZE ),
+            1 => self.cpu.set_relative_offset( value as i64 / CELL_SIZE as i64 ),
+            _ => return false
+        }
+
+        return true;
+    }
+
+    fn stop_reply( &self, signal: u8 ) -> String {
+        if self.cpu.has_finished() {
+            return "W00".to_string();
+        }
+
+        return format!( "S{:02x}", signal );
+    }
+
+    // the cpu panics on an ip that is not an instruction with all of its operands in memory,
+    // which the debugger can easily set up by writing the registers
+    fn can_execute( &self ) -> bool {
+        let ip = self.cpu.get_instruction_pointer();
+        let memory = self.cpu.get_memory();
+
+        let count = match memory.get( &ip ).and_then( |opcode| parameter_count( opcode % 100 ) ) {
+            Some( count ) => count,
+            None => return false
+        };
+
+        return match ip.checked_add( count ) {
+            Some( end ) => ( ip..=end ).all( |a| memory.contains_key( &a ) ),
+            None => false
+        };
+    }
+
+    pub fn step( &mut self ) -> String {
+        if self.cpu.has_finished() {
+            return self.stop_reply( 5 );
+        }
+
+        if !self.can_execute() {
+            return "E01".to_string();
+        }
+
+        self.cpu.execute_instruction();
+
+        return self.stop_reply( 5 );
+    }
+
+    /// Run until a breakpoint, the program halting, or `interrupted` returning true.
+    pub fn resume<F: FnMut() -> bool>( &mut self, mut interrupted: F ) -> String {
+        let mut steps: u64 = 0;
+
+        while !self.cpu.has_finished() {
+            // stop in front of anything that cannot run so the debugger can look at it
+            if !self.can_execute() {
+                return self.stop_reply( 5 );
+            }
+
+            self.cpu.execute_instruction();
+            steps += 1;
+
+            if self.breakpoints.contains( &self.cpu.get_instruction_pointer() ) {
+                return self.stop_reply( 5 );
+            }
+
+            if steps % INTERRUPT_POLL == 0 && interrupted() {
+                return self.stop_reply( 2 );
+            }
+        }
+
+        return self.stop_reply( 5 );
+    }
+
+    fn read_features( &self, request: &str ) -> String {
+        let ( _, range ) = request.split_at( request.rfind( ':' ).unwrap() + 1 );
+
+        match parse_address_length( range ) {
+            Some( ( offset, length ) ) if offset < TARGET_XML.len() => {
+                let end = usize::min( offset.saturating_add( length ), TARGET_XML.len() );
+                let prefix = if end == TARGET_XML.len() { "l" } else { "m" };
+
+                format!( "{}{}", prefix, &TARGET_XML[offset..end] )
+            },
+            Some( _ ) => "l".to_string(),
+            None => "E01".to_string()
+        }
+    }
+
+    /// Work out the reply to a single packet (without the $ and checksum framing).
+    pub fn handle_packet( &mut self, packet: &str ) -> Reply {
+        let ( command, args ) = packet.split_at( usize::min( 1, packet.len() ) );
+
+        let reply = match command {
+            "?" => self.stop_reply( 5 ),
+            "g" => format!( "{}{}", hex_u64( self.read_register( 0 ).unwrap() ), hex_u64( self.read_register( 1 ).unwrap() ) ),
+            "G" => {
+                let ip = args.get( 0..16 ).and_then( parse_hex_le );
+                let rb = args.get( 16..32 ).and_then( parse_hex_le );
+
+                match ( ip, rb ) {
+                    ( Some( ip ), Some( rb ) ) => {
+                        self.write_register( 0, ip );
+                        self.write_register( 1, rb );
+                        "OK".to_string()
+                    },
+                    _ => "E01".to_string()
+                }
+            },
+            "p" => match usize::from_str_radix( args, 16 ).ok().and_then( |r| self.read_register( r ) ) {
+                Some( value ) => hex_u64( value ),
+                None => "E01".to_string()
+            },
+            "P" => {
+                let mut parts = args.split( '=' );
+                let register = parts.next().and_then( |r| usize::from_str_radix( r, 16 ).ok() );
+                let value = parts.next().and_then( parse_hex_le );
+
+                match ( register, value ) {
+                    ( Some( r ), Some( v ) ) if self.write_register( r, v ) => "OK".to_string(),
+                    _ => "E01".to_string()
+                }
+            },
+            "m" => {
+                // two hex digits a byte, so this is as much as fits in one reply
+                let range = parse_address_length( args )
+                    .map( |( address, length )| ( address, usize::min( length, PACKET_SIZE / 2 ) ) )
+                    .and_then( |( address, length )| Some( address..address.checked_add( length )? ) );
+
+                match range {
+                    Some( range ) => range.map( |a| format!( "{:02x}", self.read_byte( a ) ) ).collect(),
+                    None => "E01".to_string()
+                }
+            },
+            "M" => {
+                let mut parts = args.split( ':' );
+                let range = parts.next().and_then( parse_address_length );
+                let data = parts.next().unwrap_or( "" );
+
+                let bytes: Option<Vec<u8>> = ( 0..data.len() / 2 )
+                    .map( |i| data.get( i * 2..i * 2 + 2 ).and_then( |b| u8::from_str_radix( b, 16 ).ok() ) )
+                    .collect();
+
+                match ( range, bytes ) {
+                    ( Some( ( address, length ) ), Some( bytes ) ) if data.len() == length * 2 && address.checked_add( length ).is_some() => {
+                        for ( i, byte ) in bytes.into_iter().enumerate() {
+                            self.write_byte( address + i, byte );
+                        }
+
+                        "OK".to_string()
+                    },
+                    _ => "E01".to_string()
+                }
+            },
+            "Z" | "z" => {
+                let mut parts = args.split( ',' );
+                let kind = parts.next().unwrap_or( "" );
+                let address = parts.next().and_then( |a| usize::from_str_radix( a, 16 ).ok() );
+
+                match ( kind, address ) {
+                    ( "0", Some( a ) ) | ( "1", Some( a ) ) => {
+                        if command == "Z" {
+                            self.breakpoints.insert( a / CELL_SIZE );
+                        } else {
+                            self.breakpoints.remove( &( a / CELL_SIZE ) );
+                        }
+
+                        "OK".to_string()
+                    },
+                    // watchpoints are not supported
+                    _ => String::new()
+                }
+            },
+            "s" => self.step(),
+            "c" => return Reply::Continue,
+            "H" => "OK".to_string(),
+            "D" | "k" => return Reply::Close,
+            "q" => {
+                if args.starts_with( "Supported" ) {
+                    format!( "PacketSize={:x};qXfer:features:read+", PACKET_SIZE )
+                } else if args.starts_with( "Xfer:features:read:target.xml:" ) {
+                    self.read_features( args )
+                } else if args == "Attached" {
+                    "1".to_string()
+                } else if args == "C" {
+                    "QC1".to_string()
+                } else if args == "fThreadInfo" {
+                    "m1".to_string()
+                } else if args == "sThreadInfo" {
+                    "l".to_string()
+                } else {
+                    String::new()
+                }
+            },
+            _ => String::new()
+        };
+
+        return Reply::Packet( reply );
+    }
+
+    fn read_packet( stream: &mut TcpStream ) -> io::Result< Option<String> > {
+        let mut byte = [0u8; 1];
+
+        // a packet with a bad checksum is nacked and the client sends it again
+        loop {
+            let mut packet = String::new();
+
+            // skip acks and anything else until the start of a packet
+            loop {
+                if stream.read( &mut byte )? == 0 {
+                    return Ok( None );
+                }
+
+                if byte[0] == b'$' {
+                    break;
+                }
+            }
+
+            loop {
+                if stream.read( &mut byte )? == 0 {
+                    return Ok( None );
+                }
+
+                if byte[0] == b'#' {
+                    break;
+                }
+
+                packet.push( byte[0] as char );
+            }
+
+            let mut received_checksum = [0u8; 2];
+            stream.read_exact( &mut received_checksum )?;
+
+            if std::str::from_utf8( &received_checksum ).ok() != Some( checksum( &packet ).as_str() ) {
+                stream.write_all( b"-" )?;
+                continue;
+            }
+
+            stream.write_all( b"+" )?;
+
+            return Ok( Some( packet ) );
+        }
+    }
+
+    fn poll_interrupt( stream: &mut TcpStream ) -> bool {
+        let mut byte = [0u8; 1];
+
+        if stream.set_nonblocking( true ).is_err() {
+            return false;
+        }
+
+        let interrupted = matches!( stream.read( &mut byte ), Ok( 1 ) if byte[0] == 0x03 );
+        stream.set_nonblocking( false ).ok();
+
+        return interrupted;
+    }
+
+    pub fn serve_stream( &mut self, mut stream: TcpStream ) -> io::Result<()> {
+        while let Some( packet ) = Self::read_packet( &mut stream )? {
+            let reply = match self.handle_packet( &packet ) {
+                Reply::Packet( reply ) => reply,
+                Reply::Continue => {
+                    let mut poll_stream = stream.try_clone()?;
+                    self.resume( || Self::poll_interrupt( &mut poll_stream ) )
+                },
+                Reply::Close => {
+                    stream.write_all( frame_packet( "OK" ).as_bytes() )?;
+                    return Ok( () );
+                }
+            };
+
+            stream.write_all( frame_packet( &reply ).as_bytes() )?;
+        }
+
+        return Ok( () );
+    }
+
+    /// Listen on localhost and serve a single debugger session, e.g. `target remote :1234`.
+    pub fn serve( &mut self, port: u16 ) -> io::Result<()> {
+        let listener = TcpListener::bind( ( "127.0.0.1", port ) )?;
+        let ( stream, _ ) = listener.accept()?;
+
+        return self.serve_stream( stream );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::prelude::*;
+    use std::net::{
+        TcpListener,
+        TcpStream
+    };
+    use std::thread;
+
+    use super::*;
+    use crate::intcode::devices::{
+        IteratorConsumer,
+        VecProducer
+    };
+    use crate::intcode::process_input;
+
+    // adds 2 and 3 into cell 7, prints it and halts
+    const PROGRAM: &str = "1101,2,3,7,4,7,99,0";
+
+    // sends a packet the way gdb does and reads back the ack and the framed reply
+    fn exchange( stream: &mut TcpStream, packet: &str ) -> String {
+        stream.write_all( frame_packet( packet ).as_bytes() ).unwrap();
+
+        let mut byte = [0u8; 1];
+        stream.read_exact( &mut byte ).unwrap();
+        assert_eq!( byte[0], b'+', "packet {} was not acknowledged", packet );
+
+        stream.read_exact( &mut byte ).unwrap();
+        assert_eq!( byte[0], b'$' );
+
+        let mut reply = String::new();
+
+        loop {
+            stream.read_exact( &mut byte ).unwrap();
+
+            if byte[0] == b'#' {
+                break;
+            }
+
+            reply.push( byte[0] as char );
+        }
+
+        let mut received_checksum = [0u8; 2];
+        stream.read_exact( &mut received_checksum ).unwrap();
+        assert_eq!( std::str::from_utf8( &received_checksum ).unwrap(), checksum( &reply ) );
+        stream.write_all( b"+" ).unwrap();
+
+        return reply;
+    }
+
+    #[test]
+    fn loopback_session() {
+        let listener = TcpListener::bind( ( "127.0.0.1", 0 ) ).unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = thread::spawn( move || {
+            let cpu = CPU::initialise( process_input( PROGRAM ), IteratorConsumer::initialise( vec![] ), VecProducer::initialise() );
+            let mut stub = GdbStub::initialise( cpu );
+            stub.get_cpu().display_std_out( false );
+
+            let ( stream, _ ) = listener.accept().unwrap();
+            stub.serve_stream( stream ).unwrap();
+
+            return stub.get_cpu().get_outputs_producer().take();
+        } );
+
+        let mut client = TcpStream::connect( ( "127.0.0.1", port ) ).unwrap();
+
+        assert!( exchange( &mut client, "qSupported:multiprocess+;xmlRegisters=i386" ).starts_with( "PacketSize=4000;" ) );
+        assert!( exchange( &mut client, "qXfer:features:read:target.xml:0,ffb" ).contains( "org.aoc.intcode" ) );
+        assert_eq!( exchange( &mut client, "?" ), "S05" );
+        assert_eq!( exchange( &mut client, "g" ), "00000000000000000000000000000000" );
+
+        // cell 1 holds 2, as 8 little endian bytes
+        assert_eq!( exchange( &mut client, "m8,8" ), "0200000000000000" );
+        assert_eq!( exchange( &mut client, "mfffffffffffffff8,10" ), "E01" );
+        assert_eq!( exchange( &mut client, "m0,100000" ).len(), PACKET_SIZE );
+
+        // break on the output instruction, run to it and check the sum landed in cell 7
+        assert_eq!( exchange( &mut client, "Z0,20,8" ), "OK" );
+        assert_eq!( exchange( &mut client, "c" ), "S05" );
+        assert_eq!( exchange( &mut client, "p0" ), "2000000000000000" );
+        assert_eq!( exchange( &mut client, "m38,8" ), "0500000000000000" );
+
+        // an ip that is not an instruction stops the debugger rather than the stub
+        assert_eq!( exchange( &mut client, "P0=0001000000000000" ), "OK" );
+        assert_eq!( exchange( &mut client, "s" ), "E01" );
+        assert_eq!( exchange( &mut client, "c" ), "S05" );
+        assert_eq!( exchange( &mut client, "G00" ), "E01" );
+        assert_eq!( exchange( &mut client, "G20000000000000000000000000000000" ), "OK" );
+
+        assert_eq!( exchange( &mut client, "s" ), "S05" );
+        assert_eq!( exchange( &mut client, "c" ), "W00" );
+        assert_eq!( exchange( &mut client, "k" ), "OK" );
+
+        assert_eq!( server.join().unwrap(), vec![ 5 ] );
+    }
+
+    #[test]
+    fn corrupt_packets_are_nacked_until_a_good_one_arrives() {
+        let listener = TcpListener::bind( ( "127.0.0.1", 0 ) ).unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = thread::spawn( move || {
+            let ( mut stream, _ ) = listener.accept().unwrap();
+            return GdbStub::<IteratorConsumer<std::vec::IntoIter<i64>>, VecProducer>::read_packet( &mut stream ).unwrap();
+        } );
+
+        let mut client = TcpStream::connect( ( "127.0.0.1", port ) ).unwrap();
+        let corrupt = "$?#00".repeat( 10_000 );
+        client.write_all( corrupt.as_bytes() ).unwrap();
+        client.write_all( frame_packet( "g" ).as_bytes() ).unwrap();
+
+        let mut acks = vec![0u8; 10_001];
+        client.read_exact( &mut acks ).unwrap();
+
+        assert!( acks[..10_000].iter().all( |a| *a == b'-' ) );
+        assert_eq!( acks[10_000], b'+' );
+        assert_eq!( server.join().unwrap(), Some( "g".to_string() ) );
+    }
+}
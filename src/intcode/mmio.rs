@@ -0,0 +1,145 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::time::Instant;
+
+/// A device that sits on a range of addresses in the CPU memory. Any parameter read or
+/// write that lands inside the mapped range is forwarded to the device instead of memory.
+/// The offset passed in is relative to the start of the mapped range.
+pub trait MemoryDevice {
+    fn read( &mut self, offset: usize ) -> i64;
+    fn write( &mut self, offset: usize, value: i64 );
+}
+
+#[derive(Clone)]
+pub struct MappedDevice {
+    start: usize,
+    end: usize,
+    device: Rc<RefCell<dyn MemoryDevice>>
+}
+
+impl MappedDevice {
+    #[allow(dead_code)]
+    pub fn initialise( start: usize, end: usize, device: Rc<RefCell<dyn MemoryDevice>> ) -> Self {
+        if start >= end {
+            panic!( "device range {}..{} is empty", start, end );
+        }
+
+        return MappedDevice { start: start, end: end, device: device };
+    }
+
+    pub fn contains( &self, idx: usize ) -> bool {
+        return idx >= self.start && idx < self.end;
+    }
+
+    #[allow(dead_code)]
+    pub fn overlaps( &self, start: usize, end: usize ) -> bool {
+        return start < self.end && self.start < end;
+    }
+
+    pub fn read( &self, idx: usize ) -> i64 {
+        return self.device.borrow_mut().read( idx - self.start );
+    }
+
+    pub fn write( &self, idx: usize, value: i64 ) {
+        self.device.borrow_mut().write( idx - self.start, value );
+    }
+}
+
+// width * height cells laid out row by row
+#[allow(dead_code)]
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    cells: Vec<i64>
+}
+
+#[allow(dead_code)]
+impl Framebuffer {
+    pub fn initialise( width: usize, height: usize ) -> Self {
+        return Framebuffer { width: width, height: height, cells: vec![0; width * height] };
+    }
+
+    pub fn size( &self ) -> usize {
+        return self.width * self.height;
+    }
+
+    pub fn get( &self, x: usize, y: usize ) -> i64 {
+        return self.cells[y * self.width + x];
+    }
+
+    pub fn render( &self, palette: &dyn Fn( i64 ) -> char ) -> String {
+        let mut screen = String::new();
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                screen.push( palette( self.get( x, y ) ) );
+            }
+
+            screen.push( '\n' );
+        }
+
+        return screen;
+    }
+}
+
+impl MemoryDevice for Framebuffer {
+    fn read( &mut self, offset: usize ) -> i64 {
+        return self.cells[offset];
+    }
+
+    fn write( &mut self, offset: usize, value: i64 ) {
+        self.cells[offset] = value;
+    }
+}
+
+/// Single cell device returning a new pseudo random value on every read (xorshift64).
+/// Writing to the cell reseeds the generator.
+#[allow(dead_code)]
+pub struct RandomDevice {
+    state: u64
+}
+
+#[allow(dead_code)]
+impl RandomDevice {
+    pub fn initialise( seed: u64 ) -> Self {
+        return RandomDevice { state: if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed } };
+    }
+}
+
+impl MemoryDevice for RandomDevice {
+    fn read( &mut self, _: usize ) -> i64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+
+        // keep the values positive so programs can use them with less than
+        return ( self.state >> 1 ) as i64;
+    }
+
+    fn write( &mut self, _: usize, value: i64 ) {
+        *self = RandomDevice::initialise( value as u64 );
+    }
+}
+
+/// Single cell device returning the milliseconds elapsed since the clock was created.
+/// Writes are ignored.
+#[allow(dead_code)]
+pub struct ClockDevice {
+    start: Instant
+}
+
+#[allow(dead_code)]
+impl ClockDevice {
+    pub fn initialise() -> Self {
+        return ClockDevice { start: Instant::now() };
+    }
+}
+
+impl MemoryDevice for ClockDevice {
+    fn read( &mut self, _: usize ) -> i64 {
+        return self.start.elapsed().as_millis() as i64;
+    }
+
+    fn write( &mut self, _: usize, _: i64 ) {
+    }
+}
@@ -0,0 +1,32 @@
+// shared intcode machine and the tooling built on top of it
+
+pub mod batch;
+pub mod bigcpu;
+pub mod callstack;
+pub mod conformance;
+pub mod coverage;
+pub mod cpu;
+pub mod devices;
+pub mod disasm;
+pub mod error;
+pub mod explore;
+pub mod expect;
+pub mod fuzz;
+pub mod gdbstub;
+pub mod mmio;
+pub mod observer;
+pub mod optimise;
+pub mod reverse;
+pub mod scanner;
+pub mod service;
+pub mod strings;
+pub mod transpile;
+
+use std::collections::HashMap;
+
+pub fn process_input( input: &str ) -> HashMap<usize, i64> {
+    return input.trim().split( "," )
+        .enumerate()
+        .map( |(i, v)| (i, v.trim().parse().unwrap()))
+        .collect();
+}
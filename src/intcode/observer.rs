@@ -0,0 +1,109 @@
+use std::fmt;
+
+/// Hooks called by the CPU as it runs, implement only the ones you need. Observers are
+/// registered with `add_observer`; while none are the CPU skips all of this, so an
+/// unobserved machine pays one empty check per hook.
+///
+/// Memory reads are the data reads made by position and relative parameters (immediate
+/// operands are part of the fetch), memory writes are every store made by an instruction.
+pub trait Observer {
+    fn instruction_fetched( &mut self, _idx: usize, _code: i64 ) {}
+    fn memory_read( &mut self, _idx: usize, _value: i64 ) {}
+    fn memory_written( &mut self, _idx: usize, _old: i64, _new: i64 ) {}
+    fn input_consumed( &mut self, _value: i64 ) {}
+    fn output_produced( &mut self, _value: i64 ) {}
+    fn halted( &mut self, _idx: usize, _steps: u64 ) {}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(dead_code)]
+pub enum Event {
+    Fetch { idx: usize, code: i64 },
+    Read { idx: usize, value: i64 },
+    Write { idx: usize, old: i64, new: i64 },
+    Input( i64 ),
+    Output( i64 ),
+    Halt { idx: usize, steps: u64 }
+}
+
+impl fmt::Display for Event {
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        match self {
+            Event::Fetch { idx, code } => write!( f, "fetch  [{}] {}", idx, code ),
+            Event::Read { idx, value } => write!( f, "read   [{}] -> {}", idx, value ),
+            Event::Write { idx, old, new } => write!( f, "write  [{}] {} -> {}", idx, old, new ),
+            Event::Input( value ) => write!( f, "input  {}", value ),
+            Event::Output( value ) => write!( f, "output {}", value ),
+            Event::Halt { idx, steps } => write!( f, "halt   at {} after {} steps", idx, steps )
+        }
+    }
+}
+
+/// Records every event, optionally only the most recent `capacity` of them.
+#[derive(Clone, Debug, Default)]
+#[allow(dead_code)]
+pub struct EventLog {
+    events: Vec<Event>,
+    capacity: Option<usize>,
+    dropped: usize
+}
+
+#[allow(dead_code)]
+impl EventLog {
+    pub fn initialise( capacity: Option<usize> ) -> Self {
+        return EventLog { events: Vec::new(), capacity: capacity, dropped: 0 };
+    }
+
+    fn push( &mut self, event: Event ) {
+        if let Some( capacity ) = self.capacity {
+            if self.events.len() >= capacity {
+                // drop the older half in one go rather than shifting on every event
+                let drop = usize::max( self.events.len() / 2, 1 );
+                self.events.drain( 0..drop );
+                self.dropped += drop;
+            }
+        }
+
+        self.events.push( event );
+    }
+
+    pub fn events( &self ) -> &Vec<Event> {
+        return &self.events;
+    }
+
+    // how many events were thrown away to stay within the capacity
+    pub fn dropped( &self ) -> usize {
+        return self.dropped;
+    }
+
+    pub fn clear( &mut self ) {
+        self.events.clear();
+        self.dropped = 0;
+    }
+}
+
+impl Observer for EventLog {
+    fn instruction_fetched( &mut self, idx: usize, code: i64 ) {
+        self.push( Event::Fetch { idx: idx, code: code } );
+    }
+
+    fn memory_read( &mut self, idx: usize, value: i64 ) {
+        self.push( Event::Read { idx: idx, value: value } );
+    }
+
+    fn memory_written( &mut self, idx: usize, old: i64, new: i64 ) {
+        self.push( Event::Write { idx: idx, old: old, new: new } );
+    }
+
+    fn input_consumed( &mut self, value: i64 ) {
+        self.push( Event::Input( value ) );
+    }
+
+    fn output_produced( &mut self, value: i64 ) {
+        self.push( Event::Output( value ) );
+    }
+
+    fn halted( &mut self, idx: usize, steps: u64 ) {
+        self.push( Event::Halt { idx: idx, steps: steps } );
+    }
+}
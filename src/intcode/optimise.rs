@@ -0,0 +1,541 @@
+use std::collections::{
+    BTreeSet,
+    HashMap
+};
+
+use super::disasm::{
+    Parameter,
+    DecodedInstruction,
+    decode,
+    find_code,
+    program_len
+};
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OptimiseOptions {
+    /// Relative mode reads and writes can land anywhere, so any program using them has no
+    /// provably untouched code. Setting this asserts that they only ever touch addresses at
+    /// or above the given one (usually the stack set up by a leading `109,N`).
+    pub trust_relative_access_above: Option<usize>
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OptimiseReport {
+    pub folded: usize,
+    pub noops_removed: usize,
+    pub jumps_threaded: usize,
+    // how much shorter the program got
+    pub cells_removed: usize
+}
+
+/// Every cell that could be written, or read as data, by some instruction. Any decodable
+/// address is treated as a possible instruction start since indirect jumps can go anywhere.
+/// Returns None when a relative access is not covered by the options, as then nothing is safe.
+fn touched_cells( program: &HashMap<usize, i64>, options: &OptimiseOptions ) -> Option<BTreeSet<usize>> {
+    let mut touched: BTreeSet<usize> = BTreeSet::new();
+
+    for address in 0..program_len( program ) {
+        let instruction = match decode( program, address ) {
+            Some( instruction ) => instruction,
+            None => continue
+        };
+
+        for parameter in instruction.parameters.iter() {
+            match parameter {
+                Parameter::Position( p ) => {
+                    if *p >= 0 {
+                        touched.insert( *p as usize );
+                    }
+                },
+                Parameter::Relative( _ ) => {
+                    options.trust_relative_access_above?;
+                },
+                // immediate operands are part of the instruction itself
+                Parameter::Immediate( _ ) => {}
+            }
+        }
+    }
+
+    if let Some( above ) = options.trust_relative_access_above {
+        touched.extend( above..usize::max( above, program_len( program ) ) );
+    }
+
+    return Some( touched );
+}
+
+fn is_noop( instruction: &DecodedInstruction ) -> bool {
+    let p = &instruction.parameters;
+
+    match instruction.opcode {
+        // x + 0 -> x and x * 1 -> x where x is the cell being written
+        1 | 2 => {
+            let identity = if instruction.opcode == 1 { 0 } else { 1 };
+
+            ( p[0] == p[2] && p[1] == Parameter::Immediate( identity ) ) ||
+            ( p[1] == p[2] && p[0] == Parameter::Immediate( identity ) )
+        },
+        5 => p[0] == Parameter::Immediate( 0 ) || unconditional_target( instruction ) == Some( instruction.address + 3 ),
+        6 => matches!( p[0], Parameter::Immediate( v ) if v != 0 ) || unconditional_target( instruction ) == Some( instruction.address + 3 ),
+        9 => p[0] == Parameter::Immediate( 0 ),
+        _ => false
+    }
+}
+
+// target of a jump that is always taken, e.g. 1105,1,x or 1106,0,x
+fn unconditional_target( instruction: &DecodedInstruction ) -> Option<usize> {
+    let always = match ( instruction.opcode, instruction.parameters.get( 0 ) ) {
+        ( 5, Some( Parameter::Immediate( v ) ) ) => *v != 0,
+        ( 6, Some( Parameter::Immediate( v ) ) ) => *v == 0,
+        _ => false
+    };
+
+    return match ( always, instruction.parameters.get( 1 ) ) {
+        ( true, Some( Parameter::Immediate( target ) ) ) if *target >= 0 => Some( *target as usize ),
+        _ => None
+    };
+}
+
+fn encode( instruction: &DecodedInstruction ) -> Vec<i64> {
+    let mut code = instruction.opcode;
+
+    for ( i, parameter ) in instruction.parameters.iter().enumerate() {
+        code += parameter.mode() * i64::pow( 10, i as u32 + 2 );
+    }
+
+    let mut cells = vec![code];
+    cells.extend( instruction.parameters.iter().map( |p| p.raw() ) );
+
+    return cells;
+}
+
+/// Cuts the no-ops out of a program and moves everything after them down, fixing up every
+/// address. Only possible when every address the program can use is known: no relative
+/// mode, every jump target immediate and no instruction written or read as data. Then the
+/// only addresses are the position operands and jump targets of the reachable code, and data
+/// cells never hold one. None when that cannot be shown, or there is nothing to cut.
+fn compact( program: &HashMap<usize, i64> ) -> Option<( HashMap<usize, i64>, usize )> {
+    let mut instructions: Vec<DecodedInstruction> = Vec::new();
+    let mut code_cells: BTreeSet<usize> = BTreeSet::new();
+    let mut touched: BTreeSet<usize> = BTreeSet::new();
+
+    for address in find_code( program, &[] ) {
+        let instruction = decode( program, address )?;
+
+        for cell in address..address + instruction.len() {
+            // overlapping instructions
+            if !code_cells.insert( cell ) {
+                return None;
+            }
+        }
+
+        if instruction.opcode == 9 {
+            return None;
+        }
+
+        if instruction.is_jump() && !matches!( instruction.parameters[1], Parameter::Immediate( t ) if t >= 0 ) {
+            return None;
+        }
+
+        for parameter in instruction.parameters.iter() {
+            match parameter {
+                Parameter::Position( p ) if *p >= 0 => {
+                    touched.insert( *p as usize );
+                },
+                Parameter::Immediate( _ ) => {},
+                _ => return None
+            }
+        }
+
+        instructions.push( instruction );
+    }
+
+    if code_cells.iter().any( |c| touched.contains( c ) ) {
+        return None;
+    }
+
+    let removed: BTreeSet<usize> = instructions.iter()
+        .filter( |i| is_noop( i ) )
+        .flat_map( |i| i.address..i.address + i.len() )
+        .collect();
+
+    if removed.is_empty() {
+        return None;
+    }
+
+    // an address of a removed cell moves to wherever the next cell that is kept ends up
+    let relocate = |address: i64| -> i64 {
+        return address - removed.range( ..address as usize ).count() as i64;
+    };
+
+    let mut compacted: HashMap<usize, i64> = program.iter()
+        .filter( |( address, _ )| !removed.contains( address ) )
+        .map( |( address, value )| ( relocate( *address as i64 ) as usize, *value ) )
+        .collect();
+
+    for instruction in instructions.iter().filter( |i| !is_noop( i ) ) {
+        let parameters = instruction.parameters.iter().enumerate()
+            .map( |( i, parameter )| match parameter {
+                Parameter::Position( p ) => Parameter::Position( relocate( *p ) ),
+                Parameter::Immediate( target ) if instruction.is_jump() && i == 1 => Parameter::Immediate( relocate( *target ) ),
+                other => *other
+            } )
+            .collect();
+
+        let moved = DecodedInstruction {
+            address: relocate( instruction.address as i64 ) as usize,
+            opcode: instruction.opcode,
+            parameters: parameters
+        };
+
+        for ( i, value ) in encode( &moved ).into_iter().enumerate() {
+            compacted.insert( moved.address + i, value );
+        }
+    }
+
+    return Some( ( compacted, removed.len() ) );
+}
+
+/// Peephole optimiser for the instructions whose cells are provably never written or read
+/// as data:
+///
+/// - add / mul / lt / eq with two immediate operands are folded to a single constant
+/// - no-ops (add 0, mul 1, arb 0, jumps that never jump or jump to the next instruction) are
+///   cut out and the rest of the program moved down, when every address in it can be found
+///   and fixed up (see `compact`)
+/// - otherwise every no-op in a run of them becomes a jump to the end of the run, so the run
+///   costs one instruction from wherever it is entered; data, jump tables and pushed return
+///   addresses refer to absolute addresses, so a program using relative mode is not moved
+/// - jumps to unconditional jumps or to no-ops are pointed straight at the final target
+///
+/// The result is plain intcode that any CPU runs unchanged.
+pub fn optimise( program: &HashMap<usize, i64>, options: &OptimiseOptions ) -> ( HashMap<usize, i64>, OptimiseReport ) {
+    return match compact( program ) {
+        Some( ( compacted, cells_removed ) ) => {
+            let noops_removed = find_code( program, &[] ).iter()
+                .filter_map( |a| decode( program, *a ) )
+                .filter( is_noop )
+                .count();
+
+            let ( optimised, report ) = optimise_in_place( &compacted, options );
+
+            ( optimised, OptimiseReport { noops_removed: report.noops_removed + noops_removed, cells_removed: cells_removed, ..report } )
+        },
+        None => optimise_in_place( program, options )
+    };
+}
+
+// the rewrites that leave every instruction where it was
+fn optimise_in_place( program: &HashMap<usize, i64>, options: &OptimiseOptions ) -> ( HashMap<usize, i64>, OptimiseReport ) {
+    let mut optimised = program.clone();
+    let mut report = OptimiseReport::default();
+
+    let touched = match touched_cells( program, options ) {
+        Some( touched ) => touched,
+        None => return ( optimised, report )
+    };
+
+    let code = find_code( program, &[] );
+
+    // instructions that can be rewritten: all of their cells are left alone by the program
+    let safe: HashMap<usize, DecodedInstruction> = code.iter()
+        .filter_map( |a| decode( program, *a ) )
+        .filter( |i| ( i.address..i.address + i.len() ).all( |c| !touched.contains( &c ) ) )
+        .map( |i| ( i.address, i ) )
+        .collect();
+
+    let write = |optimised: &mut HashMap<usize, i64>, instruction: &DecodedInstruction| {
+        for ( i, value ) in encode( instruction ).into_iter().enumerate() {
+            optimised.insert( instruction.address + i, value );
+        }
+    };
+
+    // constant folding
+    for instruction in safe.values() {
+        let p = &instruction.parameters;
+
+        if let ( Some( Parameter::Immediate( a ) ), Some( Parameter::Immediate( b ) ) ) = ( p.get( 0 ), p.get( 1 ) ) {
+            let value = match instruction.opcode {
+                1 => a.checked_add( *b ),
+                2 => a.checked_mul( *b ),
+                7 => Some( ( a < b ) as i64 ),
+                8 => Some( ( a == b ) as i64 ),
+                _ => None
+            };
+
+            if let Some( value ) = value {
+                if *b != 0 || instruction.opcode != 1 || *a != value {
+                    let folded = DecodedInstruction {
+                        address: instruction.address,
+                        opcode: 1,
+                        parameters: vec![ Parameter::Immediate( value ), Parameter::Immediate( 0 ), p[2] ]
+                    };
+
+                    write( &mut optimised, &folded );
+                    report.folded += 1;
+                }
+            }
+        }
+    }
+
+    // where execution really continues when arriving at an address
+    let resolve = |start: usize| -> usize {
+        let mut address = start;
+        let mut seen: BTreeSet<usize> = BTreeSet::new();
+
+        while let Some( instruction ) = safe.get( &address ) {
+            if !seen.insert( address ) {
+                break;
+            }
+
+            if is_noop( instruction ) {
+                address += instruction.len();
+            } else if let Some( target ) = unconditional_target( instruction ) {
+                address = target;
+            } else {
+                break;
+            }
+        }
+
+        return address;
+    };
+
+    // runs of no-ops jump to the end of the run
+    let mut addresses: Vec<usize> = safe.keys().cloned().collect();
+    addresses.sort();
+
+    for address in addresses.iter() {
+        let instruction = &safe[address];
+
+        if !is_noop( instruction ) {
+            continue;
+        }
+
+        // only start at the beginning of a run
+        let previous_noop = safe.values().any( |i| i.address + i.len() == *address && is_noop( i ) );
+
+        if previous_noop {
+            continue;
+        }
+
+        let mut run: Vec<&DecodedInstruction> = Vec::new();
+        let mut end = *address;
+
+        while let Some( next ) = safe.get( &end ) {
+            if !is_noop( next ) {
+                break;
+            }
+
+            end += next.len();
+            run.push( next );
+        }
+
+        if run.len() < 2 {
+            continue;
+        }
+
+        // each no-op is replaced within its own cells, so something jumping into the middle
+        // of the run still finds whole instructions; `arb 0` is too short for a jump and stays
+        let target = resolve( end );
+
+        for noop in run.iter().filter( |i| i.len() >= 3 ) {
+            let jump = DecodedInstruction {
+                address: noop.address,
+                opcode: 5,
+                parameters: vec![ Parameter::Immediate( 1 ), Parameter::Immediate( target as i64 ) ]
+            };
+
+            write( &mut optimised, &jump );
+            report.noops_removed += 1;
+        }
+    }
+
+    // jump threading
+    for address in addresses.iter() {
+        let instruction = &safe[address];
+
+        if !instruction.is_jump() || is_noop( instruction ) {
+            continue;
+        }
+
+        if let Parameter::Immediate( target ) = instruction.parameters[1] {
+            if target < 0 {
+                continue;
+            }
+
+            let final_target = resolve( target as usize );
+
+            if final_target != target as usize && optimised.get( &( address + 2 ) ) == Some( &target ) {
+                optimised.insert( address + 2, final_target as i64 );
+                report.jumps_threaded += 1;
+            }
+        }
+    }
+
+    return ( optimised, report );
+}
+
+/// Options for the common layout where the program moves the relative base past its own
+/// code (`109,N`) before it first uses relative mode. Only the straight line code from the
+/// start is looked at. This is an assumption about the program, not something proven.
+#[allow(dead_code)]
+pub fn stack_above_initial_base( program: &HashMap<usize, i64> ) -> OptimiseOptions {
+    let mut address = 0;
+    let mut above = None;
+
+    for _ in 0..64 {
+        let instruction = match decode( program, address ) {
+            Some( instruction ) => instruction,
+            None => break
+        };
+
+        if instruction.opcode == 9 {
+            if let Parameter::Immediate( base ) = instruction.parameters[0] {
+                if base >= program_len( program ) as i64 {
+                    above = Some( base as usize );
+                }
+            }
+
+            break;
+        }
+
+        let relative = instruction.parameters.iter().any( |p| matches!( p, Parameter::Relative( _ ) ) );
+
+        if relative || instruction.opcode == 99 || ( instruction.is_jump() && unconditional_target( &instruction ).is_none() ) {
+            break;
+        }
+
+        address = unconditional_target( &instruction ).unwrap_or( address + instruction.len() );
+    }
+
+    return OptimiseOptions { trust_relative_access_above: above };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::cpu::{
+        CPU,
+        Computer
+    };
+    use crate::intcode::devices::{
+        IteratorConsumer,
+        VecProducer
+    };
+    use crate::intcode::process_input;
+
+    const INSTRUCTION_LIMIT: u64 = 50_000_000;
+
+    // runs until the program halts or wants more input than it was given
+    fn run( program: &HashMap<usize, i64>, inputs: &[i64] ) -> Vec<i64> {
+        return run_counting( program, inputs ).0;
+    }
+
+    // the outputs and how many instructions it took
+    fn run_counting( program: &HashMap<usize, i64>, inputs: &[i64] ) -> ( Vec<i64>, u64 ) {
+        let mut cpu = CPU::initialise( program.clone(), IteratorConsumer::initialise( inputs.to_vec() ), VecProducer::initialise() );
+        let mut remaining = inputs.len();
+
+        cpu.display_std_out( false );
+
+        while !cpu.has_finished() && cpu.get_step_count() < INSTRUCTION_LIMIT {
+            if cpu.read_memory( cpu.get_instruction_pointer() ) % 100 == 3 {
+                if remaining == 0 {
+                    break;
+                }
+
+                remaining -= 1;
+            }
+
+            cpu.execute_instruction();
+        }
+
+        return ( cpu.get_outputs_producer().take(), cpu.get_step_count() );
+    }
+
+    fn ascii( text: &str ) -> Vec<i64> {
+        return text.chars().map( |c| c as i64 ).collect();
+    }
+
+    // the original and both optimised versions print the same for every input
+    fn check_equivalent( program: &str, inputs: &[Vec<i64>] ) -> usize {
+        let program = process_input( program );
+        let mut rewrites = 0;
+
+        for options in [ OptimiseOptions::default(), stack_above_initial_base( &program ) ].iter() {
+            let ( optimised, report ) = optimise( &program, options );
+            rewrites += report.folded + report.noops_removed + report.jumps_threaded + report.cells_removed;
+
+            for input in inputs.iter() {
+                assert_eq!( run( &program, input ), run( &optimised, input ), "outputs differ for input {:?} with {:?}", input, options );
+            }
+        }
+
+        return rewrites;
+    }
+
+    #[test]
+    fn noops_cut_out() {
+        // counts down from 3 in cell 30, with an add 0, a mul 1 and a jump to the next
+        // instruction inside the loop
+        let program = process_input( "1101,0,3,30,1001,30,0,30,1002,30,1,30,1105,1,15,4,30,1001,30,-1,30,1005,30,4,99" );
+        let ( optimised, report ) = optimise( &program, &OptimiseOptions::default() );
+
+        assert_eq!( report.noops_removed, 3 );
+        assert_eq!( report.cells_removed, 11 );
+        assert_eq!( program_len( &optimised ), program_len( &program ) - 11 );
+
+        let ( outputs, steps ) = run_counting( &program, &[] );
+        let ( optimised_outputs, optimised_steps ) = run_counting( &optimised, &[] );
+
+        assert_eq!( outputs, vec![ 3, 2, 1 ] );
+        assert_eq!( optimised_outputs, outputs );
+        assert_eq!( optimised_steps, steps - 9 );
+    }
+
+    #[test]
+    fn relative_mode_is_not_moved() {
+        let program = process_input( "109,20,1001,30,0,30,1002,30,1,30,204,-10,99" );
+        let ( optimised, report ) = optimise( &program, &OptimiseOptions::default() );
+
+        assert_eq!( report.cells_removed, 0 );
+        assert_eq!( program_len( &optimised ), program_len( &program ) );
+        assert_eq!( run( &optimised, &[] ), run( &program, &[] ) );
+    }
+
+    #[test]
+    fn jump_into_noop_run() {
+        // jumps indirectly (through cell 12) to the second no-op of the run at 3
+        let program = "6,11,12,109,0,1105,0,0,104,42,99,0,5";
+        let ( optimised, report ) = optimise( &process_input( program ), &OptimiseOptions::default() );
+
+        assert_eq!( report.noops_removed, 1 );
+        assert_eq!( run( &optimised, &[] ), vec![ 42 ] );
+        assert_eq!( run( &optimised, &[] ), run( &process_input( program ), &[] ) );
+    }
+
+    #[test]
+    fn day_inputs_unchanged() {
+        let mut rewrites = 0;
+
+        // camera image
+        rewrites += check_equivalent( include_str!( "../day17/puzzleInput.txt" ), &[ vec![] ] );
+
+        // tractor beam
+        let points: Vec<Vec<i64>> = ( 0..10 ).flat_map( |x| ( 0..10 ).map( move |y| vec![ x, y ] ) ).collect();
+        rewrites += check_equivalent( include_str!( "../day19/puzzleInput.txt" ), &points );
+
+        // springdroid, one script that falls and one that makes it across
+        rewrites += check_equivalent( include_str!( "../day21/puzzleInput.txt" ), &[
+            ascii( "NOT A J\nWALK\n" ),
+            ascii( "NOT A J\nNOT B T\nOR T J\nNOT C T\nOR T J\nAND D J\nWALK\n" )
+        ] );
+
+        // one network computer booting
+        rewrites += check_equivalent( include_str!( "../day23/puzzleInput.txt" ), &[ vec![ 0, -1, -1, -1 ] ] );
+
+        // a few rooms of the adventure
+        rewrites += check_equivalent( include_str!( "../day25/puzzleInput.txt" ), &[ ascii( "north\nsouth\nwest\ninv\n" ) ] );
+
+        assert!( rewrites > 0, "nothing was rewritten, so nothing was checked" );
+    }
+}
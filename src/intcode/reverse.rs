@@ -0,0 +1,90 @@
+use std::collections::VecDeque;
+
+/// Everything an instruction changed, enough to put the machine back the way it was.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UndoEntry {
+    pub idx: usize,
+    pub relative_offset: i64,
+    // cells in the order they were written with the value before (None if it did not exist)
+    pub writes: Vec<( usize, Option<i64> )>,
+    pub input: Option<i64>,
+    pub out: Option<i64>,
+    pub num_output: usize,
+    pub finished: bool
+}
+
+impl UndoEntry {
+    pub fn initialise( idx: usize, relative_offset: i64, out: Option<i64>, num_output: usize, finished: bool ) -> Self {
+        return UndoEntry {
+            idx: idx,
+            relative_offset: relative_offset,
+            writes: Vec::new(),
+            input: None,
+            out: out,
+            num_output: num_output,
+            finished: finished
+        };
+    }
+}
+
+/// The undo log behind `step_back` / `run_back_to`, one entry per executed instruction.
+/// With a capacity only that many instructions can be undone, the oldest are forgotten.
+///
+/// Outputs already handed to the producer cannot be taken back, and neither can writes to
+/// mapped devices, coverage or call tracking. Inputs that are stepped back over are fed
+/// again when running forwards, so a program replays exactly.
+#[derive(Clone, Debug, Default)]
+pub struct UndoLog {
+    entries: VecDeque<UndoEntry>,
+    capacity: Option<usize>
+}
+
+impl UndoLog {
+    #[allow(dead_code)]
+    pub fn initialise( capacity: Option<usize> ) -> Self {
+        return UndoLog { entries: VecDeque::new(), capacity: capacity };
+    }
+
+    pub fn begin( &mut self, entry: UndoEntry ) {
+        if let Some( capacity ) = self.capacity {
+            while self.entries.len() >= usize::max( capacity, 1 ) {
+                self.entries.pop_front();
+            }
+        }
+
+        self.entries.push_back( entry );
+    }
+
+    pub fn record_write( &mut self, idx: usize, previous: Option<i64> ) {
+        if let Some( entry ) = self.entries.back_mut() {
+            entry.writes.push( ( idx, previous ) );
+        }
+    }
+
+    pub fn record_input( &mut self, value: i64 ) {
+        if let Some( entry ) = self.entries.back_mut() {
+            entry.input = Some( value );
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn pop( &mut self ) -> Option<UndoEntry> {
+        return self.entries.pop_back();
+    }
+
+    #[allow(dead_code)]
+    pub fn len( &self ) -> usize {
+        return self.entries.len();
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty( &self ) -> bool {
+        return self.entries.is_empty();
+    }
+
+    // addresses of the instructions that can be undone, oldest first
+    #[allow(dead_code)]
+    pub fn addresses( &self ) -> Vec<usize> {
+        return self.entries.iter().map( |e| e.idx ).collect();
+    }
+}
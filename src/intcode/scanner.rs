@@ -0,0 +1,138 @@
+use std::collections::{
+    BTreeSet,
+    HashMap
+};
+
+use super::cpu::{
+    Computer,
+    Consumer,
+    Producer
+};
+
+#[allow(dead_code)]
+pub type Snapshot = HashMap<usize, i64>;
+
+#[allow(dead_code)]
+pub fn snapshot<T: Consumer, U: Producer, C: Computer<T, U>>( cpu: &C ) -> Snapshot {
+    return cpu.get_memory().clone();
+}
+
+#[allow(dead_code)]
+fn value( snapshot: &Snapshot, address: usize ) -> i64 {
+    return *snapshot.get( &address ).unwrap_or( &0 );
+}
+
+/// Every address whose value differs between the two snapshots as ( address, before, after ),
+/// sorted by address.
+#[allow(dead_code)]
+pub fn diff( before: &Snapshot, after: &Snapshot ) -> Vec<( usize, i64, i64 )> {
+    let addresses: BTreeSet<usize> = before.keys().chain( after.keys() ).cloned().collect();
+
+    return addresses.into_iter()
+        .map( |a| ( a, value( before, a ), value( after, a ) ) )
+        .filter( |( _, old, new )| old != new )
+        .collect();
+}
+
+/// Cheat engine style search for the address of a state variable (score, ball position,
+/// droid position...). Start from a snapshot, then keep narrowing the candidates down with
+/// newer snapshots and what is known to have happened to the value in between. Cells that
+/// first show up in a later snapshot join the candidates then, reading as 0 before that.
+#[allow(dead_code)]
+pub struct MemoryScanner {
+    candidates: BTreeSet<usize>,
+    // every address any snapshot has had, so ruled out ones are not added back
+    seen: BTreeSet<usize>,
+    previous: Snapshot
+}
+
+#[allow(dead_code)]
+impl MemoryScanner {
+    pub fn initialise( snapshot: &Snapshot ) -> Self {
+        return MemoryScanner {
+            candidates: snapshot.keys().cloned().collect(),
+            seen: snapshot.keys().cloned().collect(),
+            previous: snapshot.clone()
+        };
+    }
+
+    /// Keep the candidates where the predicate holds for ( previous value, new value ),
+    /// the new snapshot then becomes the one to compare against next time.
+    pub fn filter<F: Fn( i64, i64 ) -> bool>( &mut self, snapshot: &Snapshot, predicate: F ) -> usize {
+        for address in snapshot.keys() {
+            if self.seen.insert( *address ) {
+                self.candidates.insert( *address );
+            }
+        }
+
+        let previous = &self.previous;
+
+        self.candidates.retain( |a| predicate( value( previous, *a ), value( snapshot, *a ) ) );
+        self.previous = snapshot.clone();
+
+        return self.candidates.len();
+    }
+
+    pub fn changed( &mut self, snapshot: &Snapshot ) -> usize {
+        return self.filter( snapshot, |old, new| old != new );
+    }
+
+    pub fn unchanged( &mut self, snapshot: &Snapshot ) -> usize {
+        return self.filter( snapshot, |old, new| old == new );
+    }
+
+    pub fn increased( &mut self, snapshot: &Snapshot ) -> usize {
+        return self.filter( snapshot, |old, new| new > old );
+    }
+
+    pub fn decreased( &mut self, snapshot: &Snapshot ) -> usize {
+        return self.filter( snapshot, |old, new| new < old );
+    }
+
+    pub fn equals( &mut self, snapshot: &Snapshot, expected: i64 ) -> usize {
+        return self.filter( snapshot, |_, new| new == expected );
+    }
+
+    pub fn candidates( &self ) -> Vec<usize> {
+        return self.candidates.iter().cloned().collect();
+    }
+
+    // candidates with their value in the latest snapshot
+    pub fn values( &self ) -> Vec<( usize, i64 )> {
+        return self.candidates.iter()
+            .map( |a| ( *a, value( &self.previous, *a ) ) )
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot_of( cells: &[( usize, i64 )] ) -> Snapshot {
+        return cells.iter().cloned().collect();
+    }
+
+    #[test]
+    fn finds_cell_allocated_later() {
+        let mut scanner = MemoryScanner::initialise( &snapshot_of( &[ ( 0, 5 ), ( 1, 5 ) ] ) );
+
+        // the score cell at 100 only appears once the program first writes it
+        scanner.increased( &snapshot_of( &[ ( 0, 5 ), ( 1, 6 ), ( 100, 3 ) ] ) );
+        assert_eq!( scanner.candidates(), vec![ 1, 100 ] );
+
+        scanner.increased( &snapshot_of( &[ ( 0, 5 ), ( 1, 6 ), ( 100, 4 ) ] ) );
+        assert_eq!( scanner.values(), vec![ ( 100, 4 ) ] );
+    }
+
+    #[test]
+    fn ruled_out_cell_stays_out() {
+        let mut scanner = MemoryScanner::initialise( &snapshot_of( &[ ( 0, 1 ) ] ) );
+
+        scanner.changed( &snapshot_of( &[ ( 0, 1 ), ( 7, 0 ) ] ) );
+        assert!( scanner.candidates().is_empty() );
+
+        scanner.unchanged( &snapshot_of( &[ ( 0, 1 ), ( 7, 0 ) ] ) );
+        assert!( scanner.candidates().is_empty() );
+    }
+}
@@ -0,0 +1,265 @@
+use std::cell::{
+    Cell,
+    RefCell
+};
+use std::collections::{
+    HashMap,
+    VecDeque
+};
+use std::io;
+use std::io::prelude::*;
+use std::io::{
+    BufReader,
+    BufWriter
+};
+use std::net::{
+    TcpListener,
+    TcpStream
+};
+#[cfg(unix)]
+use std::os::unix::fs::FileTypeExt;
+#[cfg(unix)]
+use std::os::unix::net::{
+    UnixListener,
+    UnixStream
+};
+use std::rc::Rc;
+use std::thread;
+
+use super::cpu::{
+    CPU,
+    Computer,
+    Consumer,
+    Producer
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// Every byte received is one input, outputs below 128 are sent back as bytes and
+    /// anything larger as a decimal number on its own line (day 17, day 21, day 25).
+    Ascii,
+    /// Inputs are integers separated by commas or whitespace, every output is sent back
+    /// as a decimal number on its own line (day 13, or anything else numeric).
+    Numbers
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ServiceOptions {
+    pub encoding: Encoding,
+    pub instruction_limit: Option<u64>
+}
+
+/// A stream a machine can be served over, it has to be split into a reading and a writing half.
+pub trait Connection: Read + Write + Send + Sized + 'static {
+    fn duplicate( &self ) -> io::Result<Self>;
+}
+
+impl Connection for TcpStream {
+    fn duplicate( &self ) -> io::Result<Self> {
+        return self.try_clone();
+    }
+}
+
+#[cfg(unix)]
+impl Connection for UnixStream {
+    fn duplicate( &self ) -> io::Result<Self> {
+        return self.try_clone();
+    }
+}
+
+type SharedWriter<S> = Rc<RefCell<BufWriter<S>>>;
+
+struct StreamConsumer<S: Connection> {
+    reader: BufReader<S>,
+    writer: SharedWriter<S>,
+    encoding: Encoding,
+    pending: VecDeque<i64>,
+    closed: Rc<Cell<bool>>
+}
+
+impl<S: Connection> StreamConsumer<S> {
+    fn fill( &mut self ) -> io::Result<bool> {
+        // the client has to see the prompt before it can answer it
+        self.writer.borrow_mut().flush()?;
+
+        while self.pending.is_empty() {
+            match self.encoding {
+                Encoding::Ascii => {
+                    let mut byte = [0u8; 1];
+
+                    if self.reader.read( &mut byte )? == 0 {
+                        return Ok( false );
+                    }
+
+                    self.pending.push_back( byte[0] as i64 );
+                },
+                Encoding::Numbers => {
+                    let mut line = String::new();
+
+                    if self.reader.read_line( &mut line )? == 0 {
+                        return Ok( false );
+                    }
+
+                    for token in line.split( |c: char| c == ',' || c.is_whitespace() ).filter( |t| !t.is_empty() ) {
+                        match token.parse() {
+                            Ok( value ) => self.pending.push_back( value ),
+                            Err( _ ) => writeln!( self.writer.borrow_mut(), "not a number: {}", token )?
+                        }
+                    }
+
+                    self.writer.borrow_mut().flush()?;
+                }
+            }
+        }
+
+        return Ok( true );
+    }
+}
+
+impl<S: Connection> Consumer for StreamConsumer<S> {
+    fn get_value( &mut self ) -> i64 {
+        match self.fill() {
+            Ok( true ) => self.pending.pop_front().unwrap(),
+            // the session loop stops the machine once the client has gone
+            _ => {
+                self.closed.set( true );
+                0
+            }
+        }
+    }
+}
+
+struct StreamProducer<S: Connection> {
+    writer: SharedWriter<S>,
+    encoding: Encoding,
+    closed: Rc<Cell<bool>>
+}
+
+impl<S: Connection> Producer for StreamProducer<S> {
+    fn load_value( &mut self, value: i64 ) {
+        let mut writer = self.writer.borrow_mut();
+
+        let result = match self.encoding {
+            Encoding::Ascii if ( 0..128 ).contains( &value ) => writer.write_all( &[value as u8] ),
+            _ => writeln!( writer, "{}", value )
+        };
+
+        if result.is_err() {
+            self.closed.set( true );
+        }
+    }
+}
+
+/// Run one fresh copy of the program against a connected client until the program halts,
+/// the client disconnects or the instruction limit runs out.
+pub fn serve_connection<S: Connection>( program: HashMap<usize, i64>, stream: S, options: ServiceOptions ) -> io::Result<()> {
+    let writer = Rc::new( RefCell::new( BufWriter::new( stream.duplicate()? ) ) );
+    let closed = Rc::new( Cell::new( false ) );
+
+    let consumer = StreamConsumer {
+        reader: BufReader::new( stream ),
+        writer: writer.clone(),
+        encoding: options.encoding,
+        pending: VecDeque::new(),
+        closed: closed.clone()
+    };
+
+    let producer = StreamProducer {
+        writer: writer.clone(),
+        encoding: options.encoding,
+        closed: closed.clone()
+    };
+
+    let mut cpu = CPU::initialise( program, consumer, producer );
+    cpu.display_std_out( false );
+    cpu.pause_execution_on_output( false );
+    cpu.set_instruction_limit( options.instruction_limit );
+
+    while !cpu.has_finished() && cpu.get_error().is_none() && !closed.get() {
+        cpu.execute_instruction();
+    }
+
+    if let Some( error ) = cpu.get_error() {
+        writeln!( writer.borrow_mut(), "{}", error ).ok();
+    }
+
+    return writer.borrow_mut().flush();
+}
+
+fn spawn_session<S: Connection>( program: &HashMap<usize, i64>, stream: S, options: ServiceOptions ) {
+    let program = program.clone();
+
+    thread::spawn( move || {
+        if let Err( e ) = serve_connection( program, stream, options ) {
+            println!( "session ended with an error: {}", e );
+        }
+    });
+}
+
+/// Serve the program on localhost, every client gets its own machine on its own thread,
+/// e.g. `nc localhost 7000` to play day 25.
+pub fn serve_tcp( program: &HashMap<usize, i64>, port: u16, options: ServiceOptions ) -> io::Result<()> {
+    let listener = TcpListener::bind( ( "127.0.0.1", port ) )?;
+
+    // a client that fails to connect, or hangs up straight away, only loses its own session
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok( stream ) => stream,
+            Err( e ) => {
+                println!( "failed to accept a client: {}", e );
+                continue;
+            }
+        };
+
+        match stream.peer_addr() {
+            Ok( address ) => println!( "client connected from {}", address ),
+            Err( e ) => {
+                println!( "client disconnected before the session started: {}", e );
+                continue;
+            }
+        }
+
+        spawn_session( program, stream, options );
+    }
+
+    return Ok( () );
+}
+
+/// Same as `serve_tcp` on a unix socket. An old socket file at the path is replaced,
+/// anything else there is left alone and reported as already existing.
+#[cfg(unix)]
+pub fn serve_unix( program: &HashMap<usize, i64>, path: &str, options: ServiceOptions ) -> io::Result<()> {
+    match std::fs::symlink_metadata( path ) {
+        Ok( metadata ) if metadata.file_type().is_socket() => std::fs::remove_file( path )?,
+        Ok( _ ) => return Err( io::Error::new( io::ErrorKind::AlreadyExists, format!( "{} exists and is not a socket", path ) ) ),
+        Err( e ) if e.kind() == io::ErrorKind::NotFound => {},
+        Err( e ) => return Err( e )
+    }
+
+    let listener = UnixListener::bind( path )?;
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok( stream ) => {
+                println!( "client connected on {}", path );
+                spawn_session( program, stream, options );
+            },
+            Err( e ) => println!( "failed to accept a client: {}", e )
+        }
+    }
+
+    return Ok( () );
+}
+
+/// `serve <port>` for tcp or `serve <path>` for a unix socket, as used by the days below.
+pub fn serve( program: &HashMap<usize, i64>, address: &str, options: ServiceOptions ) -> io::Result<()> {
+    if let Ok( port ) = address.parse::<u16>() {
+        return serve_tcp( program, port, options );
+    }
+
+    #[cfg(unix)]
+    return serve_unix( program, address, options );
+
+    #[cfg(not(unix))]
+    return Err( io::Error::new( io::ErrorKind::InvalidInput, "expected a port number" ) );
+}
@@ -0,0 +1,282 @@
+use std::cell::RefCell;
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+    HashMap
+};
+use std::fmt;
+use std::rc::Rc;
+
+use super::cpu::{
+    CPU,
+    Computer
+};
+use super::devices::{
+    IteratorConsumer,
+    VecProducer
+};
+use super::disasm::{
+    Line,
+    cell,
+    disassemble_with_code,
+    find_code
+};
+use super::observer::Observer;
+
+/// How a string is laid out in memory.
+///  - Plain: a run of ASCII cells
+///  - Prefixed: a length cell followed by that many ASCII cells
+///  - IndexKeyed: a length cell followed by cells that decode as `cell + index + length`,
+///    the scheme day 25 uses for its room descriptions and item names
+///  - Traced: seen being decoded and printed while running the program
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    Plain,
+    Prefixed,
+    IndexKeyed,
+    Traced
+}
+
+/// A string found in the program. `address` is the first cell of the table (the length
+/// cell for prefixed encodings, the first character for traced ones), `length` the number
+/// of cells it takes up.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StringTable {
+    pub address: usize,
+    pub length: usize,
+    pub encoding: Encoding,
+    pub text: String
+}
+
+impl fmt::Display for StringTable {
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        let encoding = match self.encoding {
+            Encoding::Plain => "plain",
+            Encoding::Prefixed => "prefixed",
+            Encoding::IndexKeyed => "keyed",
+            Encoding::Traced => "traced"
+        };
+
+        write!( f, "{:>6}: {:<9}{:?}", self.address, encoding, self.text )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StringOptions {
+    // shortest string worth reporting
+    pub min_len: usize,
+    // extra code entry points for the code / data split, see `find_code`
+    pub roots: Vec<usize>
+}
+
+impl Default for StringOptions {
+    fn default() -> Self {
+        return StringOptions { min_len: 4, roots: Vec::new() };
+    }
+}
+
+fn is_text( value: i64 ) -> bool {
+    return value == 10 || ( 32..127 ).contains( &value );
+}
+
+// decodes `length` cells starting at `start`, None as soon as one is not text
+fn decode_cells( program: &HashMap<usize, i64>, start: usize, length: usize, key: impl Fn( usize ) -> i64 ) -> Option<String> {
+    let mut text = String::new();
+
+    for i in 0..length {
+        let value = cell( program, start + i ) + key( i );
+
+        if !is_text( value ) {
+            return None;
+        }
+
+        text.push( value as u8 as char );
+    }
+
+    // a handful of punctuation is more likely to be numbers that happen to be in range
+    if !text.chars().any( |c| c.is_ascii_alphabetic() ) {
+        return None;
+    }
+
+    return Some( text );
+}
+
+fn data_cells( program: &HashMap<usize, i64>, roots: &[usize] ) -> BTreeSet<usize> {
+    let code = find_code( program, roots );
+
+    return disassemble_with_code( program, &code ).iter()
+        .filter_map( |line| match line {
+            Line::Data( address, _ ) => Some( *address ),
+            Line::Code( _ ) => None
+        } )
+        .collect();
+}
+
+// a table has to lie entirely in the data part of the program
+fn all_data( data: &BTreeSet<usize>, start: usize, length: usize ) -> bool {
+    return ( start..start + length ).all( |a| data.contains( &a ) );
+}
+
+fn prefixed_at( program: &HashMap<usize, i64>, data: &BTreeSet<usize>, address: usize, min_len: usize ) -> Option<StringTable> {
+    let length = cell( program, address );
+
+    if length < min_len as i64 || length > 1000 || !all_data( data, address, length as usize + 1 ) {
+        return None;
+    }
+
+    let length = length as usize;
+
+    if let Some( text ) = decode_cells( program, address + 1, length, |i| ( i + length ) as i64 ) {
+        return Some( StringTable { address: address, length: length + 1, encoding: Encoding::IndexKeyed, text: text } );
+    }
+
+    if let Some( text ) = decode_cells( program, address + 1, length, |_| 0 ) {
+        return Some( StringTable { address: address, length: length + 1, encoding: Encoding::Prefixed, text: text } );
+    }
+
+    return None;
+}
+
+fn plain_at( program: &HashMap<usize, i64>, data: &BTreeSet<usize>, address: usize, min_len: usize ) -> Option<StringTable> {
+    let length = ( address.. ).take_while( |a| data.contains( a ) && is_text( cell( program, *a ) ) ).count();
+
+    if length < min_len {
+        return None;
+    }
+
+    return decode_cells( program, address, length, |_| 0 )
+        .map( |text| StringTable { address: address, length: length, encoding: Encoding::Plain, text: text } );
+}
+
+/// Finds string tables in the data part of a program by their shape alone, without running
+/// anything. Tables are tried keyed first, then prefixed, then as plain runs, and never overlap.
+pub fn find_strings( program: &HashMap<usize, i64>, options: &StringOptions ) -> Vec<StringTable> {
+    let data = data_cells( program, &options.roots );
+    let mut strings = Vec::new();
+    let mut next = 0;
+
+    for address in data.iter() {
+        if *address < next {
+            continue;
+        }
+
+        let found = prefixed_at( program, &data, *address, options.min_len )
+            .or_else( || plain_at( program, &data, *address, options.min_len ) );
+
+        if let Some( table ) = found {
+            next = table.address + table.length;
+            strings.push( table );
+        }
+    }
+
+    return strings;
+}
+
+// remembers the last data cell read before each output, which is where the character came from
+struct OutputTracer {
+    data: BTreeSet<usize>,
+    last_read: Option<usize>,
+    sources: Vec<( Option<usize>, i64 )>
+}
+
+impl Observer for OutputTracer {
+    fn memory_read( &mut self, idx: usize, _value: i64 ) {
+        if self.data.contains( &idx ) {
+            self.last_read = Some( idx );
+        }
+    }
+
+    fn output_produced( &mut self, value: i64 ) {
+        self.sources.push( ( self.last_read.take(), value ) );
+    }
+}
+
+/// Runs the program and watches its decoding routine at work: every character printed is
+/// attributed to the last data cell read before it, and characters from consecutive cells
+/// are joined into strings. This finds tables whatever their encoding, but only the ones
+/// the run prints. `input` is typed in as ASCII, the run stops when the program wants more
+/// input than that, halts, or `instruction_limit` instructions have been executed.
+pub fn trace_strings( program: &HashMap<usize, i64>, input: &str, instruction_limit: u64, options: &StringOptions ) -> Vec<StringTable> {
+    let tracer = Rc::new( RefCell::new( OutputTracer {
+        data: data_cells( program, &options.roots ),
+        last_read: None,
+        sources: Vec::new()
+    } ) );
+
+    let inputs: Vec<i64> = input.chars().map( |c| c as i64 ).collect();
+    let mut remaining = inputs.len();
+    let mut cpu = CPU::initialise( program.clone(), IteratorConsumer::initialise( inputs ), VecProducer::initialise() );
+
+    cpu.display_std_out( false );
+    cpu.add_observer( tracer.clone() );
+
+    while !cpu.has_finished() && cpu.get_step_count() < instruction_limit {
+        if cpu.read_memory( cpu.get_instruction_pointer() ) % 100 == 3 {
+            if remaining == 0 {
+                break;
+            }
+
+            remaining -= 1;
+        }
+
+        cpu.execute_instruction();
+    }
+
+    // the same table is usually printed more than once, keep each start address once
+    let mut strings: BTreeMap<usize, StringTable> = BTreeMap::new();
+    let mut current: Option<StringTable> = None;
+
+    for ( source, value ) in tracer.borrow().sources.iter() {
+        let continues = match ( current.as_ref(), source ) {
+            ( Some( table ), Some( address ) ) => table.address + table.length == *address,
+            _ => false
+        };
+
+        if continues && is_text( *value ) {
+            let table = current.as_mut().unwrap();
+            table.length += 1;
+            table.text.push( *value as u8 as char );
+            continue;
+        }
+
+        if let Some( table ) = current.take() {
+            if table.length >= options.min_len {
+                strings.insert( table.address, table );
+            }
+        }
+
+        if let Some( address ) = source {
+            if is_text( *value ) {
+                current = Some( StringTable { address: *address, length: 1, encoding: Encoding::Traced, text: ( *value as u8 as char ).to_string() } );
+            }
+        }
+    }
+
+    if let Some( table ) = current.take() {
+        if table.length >= options.min_len {
+            strings.insert( table.address, table );
+        }
+    }
+
+    return strings.into_values().collect();
+}
+
+/// Static tables plus, when given an input to run with, the traced ones the heuristics
+/// missed. A traced string that falls inside a static table is dropped.
+pub fn extract_strings( program: &HashMap<usize, i64>, trace_input: Option<&str>, options: &StringOptions ) -> Vec<StringTable> {
+    let mut strings = find_strings( program, options );
+
+    if let Some( input ) = trace_input {
+        let known: Vec<( usize, usize )> = strings.iter().map( |t| ( t.address, t.address + t.length ) ).collect();
+
+        for table in trace_strings( program, input, 10_000_000, options ) {
+            if !known.iter().any( |( start, end )| table.address >= *start && table.address < *end ) {
+                strings.push( table );
+            }
+        }
+
+        strings.sort_by_key( |t| t.address );
+    }
+
+    return strings;
+}
@@ -0,0 +1,454 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+use super::disasm::{
+    Line,
+    Parameter,
+    DecodedInstruction,
+    disassemble,
+    program_len,
+    cell
+};
+
+// the crate's own I/O traits, a standalone program gets a copy of them at the same path
+const CPU_SOURCE: &str = include_str!( "cpu.rs" );
+
+// runtime shared by every generated program: memory with dirty tracking and a plain
+// interpreter used for anything that is no longer the original code
+const RUNTIME: &str = r#"
+use std::io::prelude::*;
+use std::io;
+
+use crate::intcode::cpu::{
+    Consumer,
+    Producer
+};
+
+pub struct Machine<T: Consumer, U: Producer> {
+    memory: Vec<i64>,
+    owner: Vec<usize>,
+    dirty: Vec<bool>,
+    ip: usize,
+    rb: i64,
+    halted: bool,
+    consumer: T,
+    producer: U
+}
+
+impl<T: Consumer, U: Producer> Machine<T, U> {
+    pub fn initialise( consumer: T, producer: U ) -> Self {
+        let mut owner = vec![usize::MAX; PROGRAM.len()];
+
+        for ( start, len ) in INSTRUCTIONS.iter() {
+            for idx in *start..*start + *len {
+                owner[idx] = *start;
+            }
+        }
+
+        return Machine {
+            memory: PROGRAM.to_vec(),
+            owner: owner,
+            dirty: vec![false; PROGRAM.len()],
+            ip: 0,
+            rb: 0,
+            halted: false,
+            consumer: consumer,
+            producer: producer
+        };
+    }
+
+    pub fn has_finished( &self ) -> bool {
+        return self.halted;
+    }
+
+    pub fn memory( &self ) -> &Vec<i64> {
+        return &self.memory;
+    }
+
+    #[inline(always)]
+    fn load( &self, idx: usize ) -> i64 {
+        return *self.memory.get( idx ).unwrap_or( &0 );
+    }
+
+    #[inline(always)]
+    fn store( &mut self, idx: usize, value: i64 ) {
+        if idx >= self.memory.len() {
+            self.memory.resize( idx + 1, 0 );
+        }
+
+        self.memory[idx] = value;
+
+        // writing into compiled code means that instruction has to be interpreted from now on
+        if idx < self.owner.len() && self.owner[idx] != usize::MAX {
+            self.dirty[self.owner[idx]] = true;
+        }
+    }
+
+    // there are no negative addresses, stop rather than wrap round to a huge one
+    #[inline(always)]
+    fn checked( &self, address: i64 ) -> usize {
+        if address < 0 {
+            panic!( "negative address {} in instruction at idx {}", address, self.ip );
+        }
+
+        return address as usize;
+    }
+
+    fn address( &self, idx: usize, mode: i64 ) -> usize {
+        match mode {
+            1 => idx,
+            2 => self.checked( self.load( idx ) + self.rb ),
+            _ => self.checked( self.load( idx ) )
+        }
+    }
+
+    fn interpret_one( &mut self ) {
+        let code = self.load( self.ip );
+        let modes = [ code / 100 % 10, code / 1_000 % 10, code / 10_000 % 10 ];
+        let a = || self.address( self.ip + 1, modes[0] );
+        let b = || self.address( self.ip + 2, modes[1] );
+        let c = || self.address( self.ip + 3, modes[2] );
+
+        // only look at the operands this instruction has, the cells after it may not be addresses
+        let ( a, b, c ) = match code % 100 {
+            1 | 2 | 7 | 8 => ( a(), b(), c() ),
+            5 | 6 => ( a(), b(), 0 ),
+            3 | 4 | 9 => ( a(), 0, 0 ),
+            _ => ( 0, 0, 0 )
+        };
+
+        match code % 100 {
+            1 => { let v = self.load( a ) + self.load( b ); self.store( c, v ); self.ip += 4; },
+            2 => { let v = self.load( a ) * self.load( b ); self.store( c, v ); self.ip += 4; },
+            3 => { let v = self.consumer.get_value(); self.store( a, v ); self.ip += 2; },
+            4 => { let v = self.load( a ); self.producer.load_value( v ); self.ip += 2; },
+            5 => { if self.load( a ) != 0 { self.ip = self.load( b ) as usize } else { self.ip += 3 } },
+            6 => { if self.load( a ) == 0 { self.ip = self.load( b ) as usize } else { self.ip += 3 } },
+            7 => { let v = ( self.load( a ) < self.load( b ) ) as i64; self.store( c, v ); self.ip += 4; },
+            8 => { let v = ( self.load( a ) == self.load( b ) ) as i64; self.store( c, v ); self.ip += 4; },
+            9 => { self.rb += self.load( a ); self.ip += 2; },
+            99 => self.halted = true,
+            op => panic!( "unknown opcode {} at idx {}", op, self.ip )
+        }
+    }
+"#;
+
+const MAIN: &str = r#"
+struct StandardConsumer {
+    tokens: Vec<i64>
+}
+
+impl Consumer for StandardConsumer {
+    fn get_value( &mut self ) -> i64 {
+        while self.tokens.is_empty() {
+            let mut buff = String::new();
+
+            if io::stdin().read_line( &mut buff ).expect( "failed to read line" ) == 0 {
+                panic!( "ran out of input" );
+            }
+
+            self.tokens = buff.split( |c: char| c == ',' || c.is_whitespace() )
+                .filter( |t| !t.is_empty() )
+                .map( |t| t.parse().unwrap() )
+                .rev()
+                .collect();
+        }
+
+        return self.tokens.pop().unwrap();
+    }
+}
+
+struct StandardProducer {}
+
+impl Producer for StandardProducer {
+    fn load_value( &mut self, value: i64 ) {
+        println!( "{}", value );
+        io::stdout().flush().ok();
+    }
+}
+
+fn main() {
+    let mut machine = Machine::initialise( StandardConsumer { tokens: Vec::new() }, StandardProducer {} );
+    machine.run();
+}
+"#;
+
+// `Consumer` and `Producer` exactly as cpu.rs declares them
+fn io_traits() -> &'static str {
+    let start = CPU_SOURCE.find( "pub trait Consumer" ).expect( "cpu.rs no longer declares Consumer" );
+    let producer = CPU_SOURCE.find( "pub trait Producer" ).expect( "cpu.rs no longer declares Producer" );
+    let end = producer + CPU_SOURCE[producer..].find( "\n}\n" ).expect( "Producer is not closed" ) + 3;
+
+    return &CPU_SOURCE[start..end];
+}
+
+// a negative position operand is only an error if the instruction runs, so it is checked then
+fn address_expression( parameter: &Parameter ) -> String {
+    match parameter {
+        Parameter::Position( p ) | Parameter::Immediate( p ) if *p < 0 => format!( "self.checked( {}i64 )", p ),
+        Parameter::Relative( r ) => format!( "self.checked( self.rb + ( {} ) )", r ),
+        other => format!( "{}", other.raw() )
+    }
+}
+
+fn read_expression( parameter: &Parameter ) -> String {
+    match parameter {
+        Parameter::Immediate( v ) => format!( "( {}i64 )", v ),
+        other => format!( "self.load( {} )", address_expression( other ) )
+    }
+}
+
+fn compile_instruction( instruction: &DecodedInstruction ) -> String {
+    let p = &instruction.parameters;
+    let next = instruction.address + instruction.len();
+
+    match instruction.opcode {
+        1 | 2 | 7 | 8 => {
+            let value = match instruction.opcode {
+                1 => format!( "{} + {}", read_expression( &p[0] ), read_expression( &p[1] ) ),
+                2 => format!( "{} * {}", read_expression( &p[0] ), read_expression( &p[1] ) ),
+                7 => format!( "( {} < {} ) as i64", read_expression( &p[0] ), read_expression( &p[1] ) ),
+                _ => format!( "( {} == {} ) as i64", read_expression( &p[0] ), read_expression( &p[1] ) )
+            };
+
+            format!( "let v = {}; self.store( {}, v ); self.ip = {};", value, address_expression( &p[2] ), next )
+        },
+        3 => format!( "let v = self.consumer.get_value(); self.store( {}, v ); self.ip = {};", address_expression( &p[0] ), next ),
+        4 => format!( "let v = {}; self.producer.load_value( v ); self.ip = {};", read_expression( &p[0] ), next ),
+        5 | 6 => format!( "if {} {} 0 {{ self.ip = {} as usize; }} else {{ self.ip = {}; }}",
+            read_expression( &p[0] ),
+            if instruction.opcode == 5 { "!=" } else { "==" },
+            read_expression( &p[1] ),
+            next ),
+        9 => format!( "self.rb += {}; self.ip = {};", read_expression( &p[0] ), next ),
+        _ => "self.halted = true;".to_string()
+    }
+}
+
+/// Generate a Rust source file that runs the program natively. Every instruction found by
+/// the disassembler is compiled into its own match arm; if the program ever writes into a
+/// compiled instruction that instruction falls back to the embedded interpreter, so self
+/// modifying code still behaves exactly like the CPU. The machine is driven through the
+/// crate's `Consumer` / `Producer` traits, so without `with_main` the file can be included as
+/// a module next to `intcode` and use the devices in devices.rs. With `with_main` the file is
+/// a standalone binary reading inputs from stdin and printing one output per line, carrying
+/// its own copy of the two traits under `intcode::cpu`.
+pub fn transpile( program: &HashMap<usize, i64>, with_main: bool ) -> String {
+    let lines = disassemble( program );
+    let len = program_len( program );
+    let mut source = String::new();
+
+    source.push_str( "// generated from an intcode program, do not edit\n" );
+    source.push_str( "#![allow(unused_parens, dead_code)]\n" );
+    source.push_str( RUNTIME );
+
+    source.push_str( "\n    pub fn run( &mut self ) {\n" );
+    source.push_str( "        while !self.halted {\n" );
+    source.push_str( "            match self.ip {\n" );
+
+    for line in lines.iter() {
+        if let Line::Code( instruction ) = line {
+            source.push_str( &format!( "                // {}\n", instruction ) );
+            source.push_str( &format!( "                {} if !self.dirty[{}] => {{ {} }},\n",
+                instruction.address,
+                instruction.address,
+                compile_instruction( instruction ) ) );
+        }
+    }
+
+    source.push_str( "                _ => self.interpret_one()\n" );
+    source.push_str( "            }\n        }\n    }\n}\n" );
+
+    let cells: Vec<String> = ( 0..len ).map( |i| cell( program, i ).to_string() ).collect();
+    let instructions: Vec<String> = lines.iter()
+        .filter_map( |l| match l {
+            Line::Code( instruction ) => Some( format!( "({}, {})", instruction.address, instruction.len() ) ),
+            Line::Data( _, _ ) => None
+        })
+        .collect();
+
+    source.push_str( &format!( "\nconst PROGRAM: [i64; {}] = [{}];\n", len, cells.join( "," ) ) );
+    source.push_str( &format!( "const INSTRUCTIONS: [(usize, usize); {}] = [{}];\n", instructions.len(), instructions.join( "," ) ) );
+
+    if with_main {
+        source.push_str( &format!( "\nmod intcode {{\n    pub mod cpu {{\n{}\n    }}\n}}\n", io_traits() ) );
+        source.push_str( MAIN );
+    }
+
+    return source;
+}
+
+/// Writes the standalone program to `path`, ready for `rustc --edition 2018 -O <path>`.
+pub fn write_transpiled( program: &HashMap<usize, i64>, path: &str ) -> io::Result<()> {
+    return fs::write( path, transpile( program, true ) );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::prelude::*;
+    use std::path::{
+        Path,
+        PathBuf
+    };
+    use std::process::{
+        Command,
+        Stdio
+    };
+
+    use super::*;
+    use crate::intcode::conformance::cases;
+    use crate::intcode::cpu::{
+        CPU,
+        Computer
+    };
+    use crate::intcode::devices::{
+        IteratorConsumer,
+        VecProducer
+    };
+    use crate::intcode::process_input;
+
+    // a directory for one test's sources and binaries, removed again when the test ends
+    struct ScratchDir {
+        path: PathBuf
+    }
+
+    impl ScratchDir {
+        fn initialise( name: &str ) -> Self {
+            let path = std::env::temp_dir().join( format!( "intcode-transpile-{}-{}", std::process::id(), name ) );
+            fs::create_dir_all( &path ).unwrap();
+
+            return ScratchDir {
+                path: path
+            };
+        }
+    }
+
+    impl Drop for ScratchDir {
+        fn drop( &mut self ) {
+            fs::remove_dir_all( &self.path ).ok();
+        }
+    }
+
+    // builds the standalone program with rustc in the scratch directory
+    fn compile( scratch: &ScratchDir, program: &HashMap<usize, i64>, name: &str ) -> PathBuf {
+        let dir = &scratch.path;
+        let source = dir.join( format!( "{}.rs", name ) );
+        let binary = dir.join( name );
+        fs::write( &source, transpile( program, true ) ).unwrap();
+
+        let output = Command::new( std::env::var( "RUSTC" ).unwrap_or_else( |_| "rustc".to_string() ) )
+            .args( [ "--edition", "2018", "-A", "warnings", "-o" ] )
+            .arg( &binary )
+            .arg( &source )
+            .output()
+            .unwrap();
+
+        assert!( output.status.success(), "{} does not compile:\n{}", name, String::from_utf8_lossy( &output.stderr ) );
+
+        return binary;
+    }
+
+    // outputs printed before the program halted or ran out of input, and whether it halted
+    fn run_native( binary: &Path, inputs: &[i64] ) -> ( Vec<i64>, bool ) {
+        let mut child = Command::new( binary )
+            .stdin( Stdio::piped() )
+            .stdout( Stdio::piped() )
+            .stderr( Stdio::piped() )
+            .spawn()
+            .unwrap();
+
+        let text: Vec<String> = inputs.iter().map( |i| i.to_string() ).collect();
+        child.stdin.take().unwrap().write_all( text.join( "\n" ).as_bytes() ).unwrap();
+
+        let output = child.wait_with_output().unwrap();
+        let outputs = String::from_utf8_lossy( &output.stdout ).lines().map( |l| l.parse().unwrap() ).collect();
+
+        return ( outputs, output.status.success() );
+    }
+
+    fn run_interpreted( program: &HashMap<usize, i64>, inputs: &[i64] ) -> ( Vec<i64>, bool ) {
+        let mut cpu = CPU::initialise( program.clone(), IteratorConsumer::initialise( inputs.to_vec() ), VecProducer::initialise() );
+        let mut remaining = inputs.len();
+
+        cpu.display_std_out( false );
+
+        while !cpu.has_finished() {
+            if cpu.read_memory( cpu.get_instruction_pointer() ) % 100 == 3 {
+                if remaining == 0 {
+                    break;
+                }
+
+                remaining -= 1;
+            }
+
+            cpu.execute_instruction();
+        }
+
+        return ( cpu.get_outputs_producer().take(), cpu.has_finished() );
+    }
+
+    #[test]
+    fn conformance_cases_match_interpreter() {
+        let scratch = ScratchDir::initialise( "conformance" );
+        let mut binaries: HashMap<&str, PathBuf> = HashMap::new();
+
+        for ( i, case ) in cases().iter().enumerate() {
+            let program = process_input( case.program );
+            let binary = binaries.entry( case.program ).or_insert_with( || compile( &scratch, &program, &format!( "case{}", i ) ) );
+
+            let native = run_native( binary, &case.inputs );
+
+            assert_eq!( native, run_interpreted( &program, &case.inputs ), "{}", case.name );
+            assert_eq!( native, ( case.outputs.clone(), true ), "{}", case.name );
+        }
+    }
+
+    #[test]
+    fn negative_position_is_a_runtime_error() {
+        // only the add at 4 reads from -1, and the jump to it is never taken
+        let scratch = ScratchDir::initialise( "negative" );
+        let program = process_input( "1105,0,4,99,1,-1,0,0,99" );
+        assert!( transpile( &program, true ).contains( "self.checked( -1i64 )" ) );
+
+        let halts = compile( &scratch, &program, "negative_unused" );
+        assert_eq!( run_native( &halts, &[] ), ( vec![], true ) );
+
+        let fails = compile( &scratch, &process_input( "1,-1,0,0,99" ), "negative_read" );
+        assert_eq!( run_native( &fails, &[] ), ( vec![], false ) );
+    }
+
+    #[test]
+    fn day_inputs_match_interpreter() {
+        let scratch = ScratchDir::initialise( "days" );
+        let beam = process_input( include_str!( "../day19/puzzleInput.txt" ) );
+        let binary = compile( &scratch, &beam, "day19" );
+
+        for x in 0..6 {
+            for y in 0..6 {
+                assert_eq!( run_native( &binary, &[ x, y ] ), run_interpreted( &beam, &[ x, y ] ), "beam at {}, {}", x, y );
+            }
+        }
+
+        // the network card boots and idles, then wants more input than it was given
+        let nic = process_input( include_str!( "../day23/puzzleInput.txt" ) );
+        let binary = compile( &scratch, &nic, "day23" );
+        let inputs = [ 3, -1, -1, -1, -1 ];
+
+        assert_eq!( run_native( &binary, &inputs ).0, run_interpreted( &nic, &inputs ).0 );
+    }
+
+    #[test]
+    fn generated_code_uses_the_crate_traits() {
+        let program = process_input( "3,0,4,0,99" );
+        let module = transpile( &program, false );
+
+        assert!( module.contains( "use crate::intcode::cpu::{" ) );
+        assert!( !module.contains( "pub trait" ) );
+
+        // the standalone copy is the declaration from cpu.rs, not a look-alike
+        assert!( transpile( &program, true ).contains( io_traits() ) );
+        assert!( io_traits().starts_with( "pub trait Consumer" ) );
+        assert!( io_traits().contains( "fn load_value( &mut self, _: i64 );\n}" ) );
+    }
+}
@@ -24,9 +24,45 @@ extern crate approx;
 #[path = "day24/challenge.rs"] mod day24;
 #[path = "day25/challenge.rs"] mod day25;
 
+mod intcode;
+
 use std::env;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    day25::day_25( args );
+    let mut args: Vec<String> = env::args().collect();
+
+    // `dayNN` as the first argument picks the day and the rest go to it, e.g.
+    // `cargo run -- day21 script src/day21/walk.expect`; without it day 25 gets everything
+    let day = args.get( 1 )
+        .and_then( |a| a.strip_prefix( "day" ) )
+        .and_then( |d| d.parse::<u32>().ok() );
+
+    if day.is_some() {
+        args.remove( 1 );
+    }
+
+    match day.unwrap_or( 25 ) {
+        5 => day05::day_05( args ),
+        6 => day06::day_06( args ),
+        7 => day07::day_07( args ),
+        8 => day08::day_08( args ),
+        9 => day09::day_09( args ),
+        10 => day10::day_10( args ),
+        11 => day11::day_11( args ),
+        12 => day12::day_12( args ),
+        13 => day13::day_13( args ),
+        14 => day14::day_14( args ),
+        15 => day15::day_15( args ),
+        16 => day16::day_16( args ),
+        17 => day17::day_17( args ),
+        18 => day18::day_18( args ),
+        19 => day19::day_19( args ),
+        20 => day20::day_20( args ),
+        21 => day21::day_21( args ),
+        22 => day22::day_22( args ),
+        23 => day23::day_23( args ),
+        24 => day24::day_24( args ),
+        25 => day25::day_25( args ),
+        other => println!( "no day {} here", other )
+    }
 }
\ No newline at end of file